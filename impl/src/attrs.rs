@@ -1,15 +1,41 @@
 use plap::group;
 use proc_macro2::TokenStream;
-use syn::parse::{Nothing, ParseStream};
-use syn::{Ident, LitBool, Type, Visibility};
+use syn::parse::{Nothing, Parse, ParseStream};
+use syn::{Expr, Ident, LitBool, Token, Type, Visibility};
 
 pub(crate) fn parse_container(input: &syn::DeriveInput) -> syn::Result<Attrs> {
     let (mut c, thisctx, thiserror) = parse_args(&input.attrs)?;
-    c.blocked_all(group![&thisctx.from, &thisctx.optional, &thiserror.source]);
+    c.blocked_all(group![
+        &thisctx.code,
+        &thisctx.default,
+        &thisctx.flatten,
+        &thisctx.from,
+        &thisctx.group,
+        &thisctx.map,
+        &thisctx.optional,
+        &thisctx.source,
+        &thiserror.backtrace,
+        &thiserror.from,
+        &thiserror.source,
+    ]);
     if matches!(input.data, syn::Data::Enum(_)) {
         c.blocked_all(group![&thisctx.rename, &thiserror.transparent]);
     } else {
-        c.blocked(&thisctx.skip);
+        c.blocked_all(group![
+            &thisctx.accessors,
+            &thisctx.constructors,
+            &thisctx.exit_code,
+            &thisctx.getter,
+            &thisctx.grpc,
+            &thisctx.grpc_details,
+            &thisctx.group_attr,
+            &thisctx.kind,
+            &thisctx.map_from,
+            &thisctx.mirror,
+            &thisctx.response,
+            &thisctx.skip,
+            &thisctx.status,
+        ]);
     }
     build_attrs(&mut c, thisctx, thiserror)
 }
@@ -17,9 +43,20 @@ pub(crate) fn parse_container(input: &syn::DeriveInput) -> syn::Result<Attrs> {
 pub(crate) fn parse_variant(input: &syn::Variant) -> syn::Result<Attrs> {
     let (mut c, thisctx, thiserror) = parse_args(&input.attrs)?;
     c.blocked_all(group![
+        &thisctx.default,
+        &thisctx.flatten,
         &thisctx.from,
+        &thisctx.getter,
+        &thisctx.group_attr,
+        &thisctx.kind,
+        &thisctx.map_from,
+        &thisctx.mirror,
         &thisctx.module,
         &thisctx.optional,
+        &thisctx.response,
+        &thisctx.source,
+        &thiserror.backtrace,
+        &thiserror.from,
         &thiserror.source,
     ]);
     build_attrs(&mut c, thisctx, thiserror)
@@ -28,17 +65,59 @@ pub(crate) fn parse_variant(input: &syn::Variant) -> syn::Result<Attrs> {
 pub(crate) fn parse_field(input: &syn::Field) -> syn::Result<Attrs> {
     let (mut c, thisctx, thiserror) = parse_args(&input.attrs)?;
     c.blocked_all(group![
+        &thisctx.accessors,
+        &thisctx.builder,
+        &thisctx.code,
+        &thisctx.constructors,
+        &thisctx.exit_code,
+        &thisctx.grpc,
+        &thisctx.grpc_details,
+        &thisctx.group,
+        &thisctx.group_attr,
+        &thisctx.kind,
+        &thisctx.map,
+        &thisctx.map_from,
+        &thisctx.mirror,
         &thisctx.module,
         &thisctx.prefix,
         &thisctx.remote,
+        &thisctx.response,
+        &thisctx.sealed,
         &thisctx.skip,
+        &thisctx.status,
         &thisctx.suffix,
-        // field visibility are the same as its parent
-        &thisctx.vis,
-        &thisctx.visibility,
         &thiserror.transparent,
     ]);
+    for (key, value) in thisctx.flatten.keys().iter().zip(thisctx.flatten.values()) {
+        if value.0.is_none() {
+            c.with_error_at(
+                key.span(),
+                format!(
+                    "`{}` requires the fields of the nested context, e.g. `{}(method, url)`",
+                    key, key
+                ),
+            );
+        }
+    }
     if input.ident.is_none() {
+        for key in thisctx.getter.keys() {
+            c.with_error_at(
+                key.span(),
+                format!("`{}` is not supported on tuple fields", key),
+            );
+        }
+        for key in thisctx.rename.keys() {
+            c.with_error_at(
+                key.span(),
+                format!("`{}` is not supported on tuple fields", key),
+            );
+        }
+        for key in thisctx.flatten.keys() {
+            c.with_error_at(
+                key.span(),
+                format!("`{}` is not supported on tuple fields", key),
+            );
+        }
         for (key, value) in thisctx
             .optional
             .keys()
@@ -62,6 +141,8 @@ fn parse_args(
     let mut c = plap::Checker::default();
     let mut thisctx = <ThisctxArgs as plap::Args>::init();
     let mut thiserror = ThiserrorArgs {
+        backtrace: plap::Arg::new("backtrace"),
+        from: plap::Arg::new("from"),
         transparent: plap::Arg::new("transparent"),
         source: plap::Arg::new("source"),
     };
@@ -89,16 +170,47 @@ fn parse_args(
             })?;
         } else if name == "source" {
             thiserror.source.add(name.clone(), Nothing);
+        } else if name == "from" {
+            thiserror.from.add(name.clone(), Nothing);
+        } else if name == "backtrace" {
+            thiserror.backtrace.add(name.clone(), Nothing);
         }
     }
 
     // checks between thisctx and thiserror attributes
-    c.conflicts_with(&thisctx.attr, &thiserror.source);
-    c.conflicts_with(&thisctx.attribute, &thiserror.source);
+    c.conflicts_with_each(&thisctx.attr, group![&thiserror.from, &thiserror.source]);
+    c.conflicts_with_each(
+        &thisctx.attribute,
+        group![&thiserror.from, &thiserror.source],
+    );
+    c.conflicts_with_each(
+        &thisctx.default,
+        group![
+            &thiserror.backtrace,
+            &thiserror.from,
+            &thiserror.source,
+            &thiserror.transparent,
+        ],
+    );
+    c.conflicts_with_each(
+        &thisctx.flatten,
+        group![
+            &thiserror.backtrace,
+            &thiserror.from,
+            &thiserror.source,
+            &thiserror.transparent,
+        ],
+    );
     c.conflicts_with_each(
         &thisctx.magic,
-        group![&thiserror.source, &thiserror.transparent],
+        group![
+            &thiserror.backtrace,
+            &thiserror.from,
+            &thiserror.source,
+            &thiserror.transparent,
+        ],
     );
+    c.conflicts_with(&thisctx.optional, &thiserror.backtrace);
 
     Ok((c, thisctx, thiserror))
 }
@@ -111,77 +223,410 @@ fn build_attrs(
     plap::Args::check(&thisctx, c);
     c.finish()?;
     let ThisctxArgs {
+        accessors,
         attr,
         attribute,
+        builder,
+        code,
+        constructors,
+        default,
+        exit_code,
+        flatten,
         from,
+        getter,
+        grpc,
+        grpc_details,
+        group,
+        group_attr,
+        kind,
         magic,
+        map,
+        map_from,
+        mirror,
         module,
         optional,
         prefix,
         remote,
         rename,
+        response,
+        sealed,
         skip,
+        source,
+        status,
         suffix,
         vis,
         visibility,
     } = thisctx;
     let ThiserrorArgs {
+        backtrace,
+        from: thiserror_from,
         transparent,
-        source,
+        source: thiserror_source,
     } = thiserror;
     Ok(Attrs {
+        accessors: accessors.take_last().map(|t| t.value()),
         attr: attr
             .take_any()
             .into_iter()
             .chain(attribute.take_any())
             .collect(),
-        from: from.take_flag(),
+        backtrace: !backtrace.is_empty(),
+        builder: builder.take_last().map(|t| t.value()),
+        code: code.take_last(),
+        constructors: constructors.take_last().map(|t| t.value()),
+        default: default.take_last().map(|t| t.0),
+        exit_code: exit_code.take_last().map(|t| t.0),
+        flatten: flatten.take_last().and_then(|t| t.0).map(|t| t.fields),
+        from: from
+            .take_last()
+            .map(|t| t.0.unwrap_or_default())
+            .filter(|t| !t.disabled),
+        getter: getter.take_last().map(|t| t.value()),
+        grpc: grpc.take_last(),
+        grpc_details: grpc_details.take_last().map(|t| t.value()),
+        group: group.take_any(),
+        group_attr: group_attr.take_any(),
+        kind: kind.take_last().map(|t| t.0),
         magic: magic.take_last().map(|t| t.value()),
+        map: map.take_last(),
+        map_from: map_from.take_any(),
+        mirror: mirror.take_last(),
         module: module.take_last(),
         optional: optional.take_last().map(|t| t.0),
         prefix: prefix.take_last(),
-        remote: remote.take_last(),
+        remote: remote.take_last().map(|t| t.types),
         rename: rename.take_last(),
+        response: response
+            .take_any()
+            .into_iter()
+            .fold(ResponseArgs::default(), |acc, t| ResponseArgs {
+                axum: acc.axum || t.axum,
+                actix: acc.actix || t.actix,
+            }),
+        sealed: sealed.take_last().map(|t| t.value()),
         skip: skip.take_last().map(|t| t.value()),
-        source: !source.is_empty(),
+        // #[from] implies the field is source
+        source: !source.is_empty() || !thiserror_source.is_empty() || !thiserror_from.is_empty(),
+        source_mode: source.take_last().and_then(|t| t.0),
+        status: status.take_last(),
         suffix: suffix.take_last(),
+        thiserror_from: !thiserror_from.is_empty(),
         transparent: !transparent.is_empty(),
         vis: vis.take_last().or_else(|| visibility.take_last()),
     })
 }
 
 pub(crate) struct Attrs {
+    // variant -> enum
+    pub accessors: Option<bool>,
     // field, struct, variant -> enum
     pub attr: Vec<TokenStream>,
+    // #[backtrace]
+    // field
+    pub backtrace: bool,
+    // struct, variant -> enum
+    pub builder: Option<bool>,
+    // variant
+    pub code: Option<CodeArg>,
+    // variant -> enum
+    pub constructors: Option<bool>,
+    // #[thisctx(default)] or #[thisctx(default = <expr>)]
     // field
-    pub from: bool,
+    pub default: Option<Option<Expr>>,
+    // #[thisctx(exit_code = <code>)] or #[thisctx(exit_code = <sysexits name>)]
+    // variant -> enum
+    pub exit_code: Option<u8>,
+    // #[thisctx(flatten(<fields>))]
+    // field
+    pub flatten: Option<Vec<Ident>>,
+    // #[thisctx(from)] or #[thisctx(from(<types>, when = <expr>))]
+    // field
+    pub from: Option<FromArgs>,
+    // #[thisctx(getter)] on enums generates getters for all shared fields
+    // field, enum
+    pub getter: Option<bool>,
+    // #[thisctx(grpc = <code>)]
+    // variant -> enum
+    pub grpc: Option<Ident>,
+    // #[thisctx(grpc_details)] attaches context fields to gRPC statuses
+    // variant -> enum
+    pub grpc_details: Option<bool>,
+    // variant
+    pub group: Vec<Ident>,
+    // enum
+    pub group_attr: Vec<TokenStream>,
+    // #[thisctx(kind)] or #[thisctx(kind = <name>)]
+    // enum
+    pub kind: Option<Option<Ident>>,
     // field -> struct, field -> variant -> enum
     pub magic: Option<bool>,
+    // variant
+    pub map: Option<MapArg>,
+    // enum
+    pub map_from: Vec<syn::Path>,
+    // enum
+    pub mirror: Option<syn::Path>,
     // struct, enum
     pub module: Option<Ident>,
     // field
     pub optional: Option<Option<Ident>>,
     // struct, variant -> enum
     pub prefix: Option<Ident>,
+    // #[thisctx(remote = <type>)] or #[thisctx(remote(<types>))]
     // struct, variant -> enum
-    pub remote: Option<Type>,
-    // struct, variant
+    pub remote: Option<Vec<Type>>,
+    // field, struct, variant
     pub rename: Option<Ident>,
+    // #[thisctx(response(axum, actix))], which may be repeated
+    // enum
+    pub response: ResponseArgs,
+    // struct, variant -> enum
+    pub sealed: Option<bool>,
     // variant -> enum
     pub skip: Option<bool>,
     // #[thisctx(source)] or #[source]
     // field
     pub source: bool,
+    // #[thisctx(source(<mode>))]
+    // field
+    pub source_mode: Option<SourceMode>,
+    // variant -> enum
+    pub status: Option<syn::LitInt>,
     // struct, variant -> enum
     pub suffix: Option<Ident>,
+    // #[from]
+    // field
+    pub thiserror_from: bool,
     // #[thisctx(transparent)] or #[error(transparent)]
     // struct, variant
     pub transparent: bool,
-    // struct, variant -> enum
+    // field, struct, variant -> enum
     pub vis: Option<Visibility>,
 }
 
+#[derive(Default)]
+pub(crate) struct FromArgs {
+    // an empty list indicates the type of the field itself
+    pub types: Vec<Type>,
+    // a predicate to select this variant for the source value
+    pub when: Option<Expr>,
+    // #[thisctx(from = false)]
+    pub disabled: bool,
+}
+
+impl Parse for FromArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        syn::custom_keyword!(when);
+
+        let mut args = Self::default();
+        // #[thisctx(from = <bool>)]
+        if input.peek(LitBool) {
+            args.disabled = !input.parse::<LitBool>()?.value;
+            return Ok(args);
+        }
+        while !input.is_empty() {
+            if input.peek(when) && input.peek2(Token![=]) {
+                let k = input.parse::<when>()?;
+                input.parse::<Token![=]>()?;
+                if args.when.is_some() {
+                    return Err(syn::Error::new(k.span, "duplicate `when`"));
+                }
+                args.when = Some(input.parse()?);
+            } else {
+                args.types.push(input.parse()?);
+            }
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
+        Ok(args)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum CodeArg {
+    // #[thisctx(code = 1001)]
+    Number(syn::LitInt),
+    // #[thisctx(code = "E_NOT_FOUND")]
+    Name(syn::LitStr),
+}
+
+impl CodeArg {
+    pub fn value(&self) -> syn::Result<String> {
+        match self {
+            Self::Number(n) => n.base10_parse::<u32>().map(|n| n.to_string()),
+            Self::Name(s) => Ok(format!("{:?}", s.value())),
+        }
+    }
+
+    pub fn span(&self) -> proc_macro2::Span {
+        match self {
+            Self::Number(n) => n.span(),
+            Self::Name(s) => s.span(),
+        }
+    }
+}
+
+impl Parse for CodeArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitInt) {
+            input.parse().map(Self::Number)
+        } else if input.peek(syn::LitStr) {
+            input.parse().map(Self::Name)
+        } else {
+            // the quoted name may be unwrapped as an identifier
+            let name = input.parse::<Ident>()?;
+            Ok(Self::Name(syn::LitStr::new(&name.to_string(), name.span())))
+        }
+    }
+}
+
+pub(crate) struct ExitCodeArg(pub u8);
+
+// the default for variants without an exit code
+pub(crate) const EX_SOFTWARE: u8 = 70;
+
+// exit codes defined in `sysexits.h`
+const SYSEXITS: &[(&str, u8)] = &[
+    ("EX_OK", 0),
+    ("EX_USAGE", 64),
+    ("EX_DATAERR", 65),
+    ("EX_NOINPUT", 66),
+    ("EX_NOUSER", 67),
+    ("EX_NOHOST", 68),
+    ("EX_UNAVAILABLE", 69),
+    ("EX_SOFTWARE", EX_SOFTWARE),
+    ("EX_OSERR", 71),
+    ("EX_OSFILE", 72),
+    ("EX_CANTCREAT", 73),
+    ("EX_IOERR", 74),
+    ("EX_TEMPFAIL", 75),
+    ("EX_PROTOCOL", 76),
+    ("EX_NOPERM", 77),
+    ("EX_CONFIG", 78),
+];
+
+impl Parse for ExitCodeArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::LitInt) {
+            return input.parse::<syn::LitInt>()?.base10_parse().map(Self);
+        }
+        let name = input.parse::<Ident>()?;
+        SYSEXITS
+            .iter()
+            .find(|(n, _)| name == n)
+            .map(|(_, code)| Self(*code))
+            .ok_or_else(|| syn::Error::new(name.span(), "expected an integer or a sysexits name"))
+    }
+}
+
+pub(crate) enum MapArg {
+    // #[thisctx(map = <variant>)]
+    Variant(Ident),
+    // #[thisctx(map = false)]
+    Skip,
+}
+
+impl Parse for MapArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitBool) {
+            let value = input.parse::<LitBool>()?;
+            if value.value {
+                return Err(syn::Error::new(value.span, "expected a variant or `false`"));
+            }
+            Ok(Self::Skip)
+        } else {
+            input.parse().map(Self::Variant)
+        }
+    }
+}
+
+pub(crate) struct FlattenArgs {
+    // the context fields of the nested struct, in their declared order
+    pub fields: Vec<Ident>,
+}
+
+impl Parse for FlattenArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fields = syn::punctuated::Punctuated::<Ident, Token![,]>::parse_terminated(input)?;
+        Ok(Self {
+            fields: fields.into_iter().collect(),
+        })
+    }
+}
+
+pub(crate) struct RemoteArgs {
+    // the first type is used as `ErrorContext::Target`
+    pub types: Vec<Type>,
+}
+
+impl Parse for RemoteArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let types = syn::punctuated::Punctuated::<Type, Token![,]>::parse_terminated(input)?;
+        if types.is_empty() {
+            return Err(input.error("expected at least 1 type"));
+        }
+        Ok(Self {
+            types: types.into_iter().collect(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ResponseArgs {
+    // axum::response::IntoResponse
+    pub axum: bool,
+    // actix_web::ResponseError
+    pub actix: bool,
+}
+
+impl Parse for ResponseArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+        for framework in syn::punctuated::Punctuated::<Ident, Token![,]>::parse_terminated(input)? {
+            if framework == "axum" {
+                args.axum = true;
+            } else if framework == "actix" {
+                args.actix = true;
+            } else {
+                return Err(syn::Error::new(
+                    framework.span(),
+                    "expected `axum` or `actix`",
+                ));
+            }
+        }
+        Ok(args)
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub(crate) enum SourceMode {
+    // #[thisctx(source(generic))]
+    Generic,
+    // #[thisctx(source(shared))]
+    Shared,
+}
+
+impl Parse for SourceMode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mode = input.parse::<Ident>()?;
+        if mode == "generic" {
+            Ok(Self::Generic)
+        } else if mode == "shared" {
+            Ok(Self::Shared)
+        } else {
+            Err(syn::Error::new(
+                mode.span(),
+                "expected `generic` or `shared`",
+            ))
+        }
+    }
+}
+
 pub(crate) struct ThiserrorArgs {
+    pub backtrace: plap::Arg<Nothing>,
+    pub from: plap::Arg<Nothing>,
     pub transparent: plap::Arg<Nothing>,
     pub source: plap::Arg<Nothing>,
 }
@@ -189,22 +634,83 @@ pub(crate) struct ThiserrorArgs {
 plap::define_args!(
     #[check(exclusive_aliases = [vis, visibility])]
     struct ThisctxArgs {
+        #[arg(is_flag)]
+        #[check(exclusive)]
+        accessors: plap::Arg<LitBool>,
+
         #[arg(is_token_tree)]
-        #[check(conflicts_with_each = [from, optional])]
+        #[check(conflicts_with_each = [from, optional, source])]
         attr: plap::Arg<TokenStream>,
 
         #[arg(is_token_tree)]
-        #[check(conflicts_with_each = [from, optional])]
+        #[check(conflicts_with_each = [from, optional, source])]
         attribute: plap::Arg<TokenStream>,
 
         #[arg(is_flag)]
+        #[check(exclusive)]
+        builder: plap::Arg<LitBool>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        code: plap::Arg<CodeArg>,
+
+        #[arg(is_flag)]
+        #[check(exclusive)]
+        constructors: plap::Arg<LitBool>,
+
+        #[arg(is_token_tree, optional)]
+        #[check(exclusive, conflicts_with_each = [flatten, from, optional])]
+        default: plap::OptionalArg<Expr>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        exit_code: plap::Arg<ExitCodeArg>,
+
+        #[arg(is_token_tree, optional)]
+        #[check(exclusive, conflicts_with_each = [from, magic, optional])]
+        flatten: plap::OptionalArg<FlattenArgs>,
+
+        #[arg(is_token_tree, optional)]
         #[check(exclusive, conflicts_with = optional)]
-        from: plap::Arg<LitBool>,
+        from: plap::OptionalArg<FromArgs>,
+
+        #[arg(is_flag)]
+        #[check(exclusive)]
+        getter: plap::Arg<LitBool>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        grpc: plap::Arg<Ident>,
+
+        #[arg(is_flag)]
+        #[check(exclusive)]
+        grpc_details: plap::Arg<LitBool>,
+
+        #[arg(is_token_tree)]
+        group: plap::Arg<Ident>,
+
+        #[arg(is_token_tree)]
+        group_attr: plap::Arg<TokenStream>,
+
+        #[arg(is_token_tree, optional)]
+        #[check(exclusive)]
+        kind: plap::OptionalArg<Ident>,
 
         #[arg(is_flag)]
         #[check(exclusive, conflicts_with_each = [from ,optional])]
         magic: plap::Arg<LitBool>,
 
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        map: plap::Arg<MapArg>,
+
+        #[arg(is_token_tree)]
+        map_from: plap::Arg<syn::Path>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive, conflicts_with_each = [constructors, remote])]
+        mirror: plap::Arg<syn::Path>,
+
         // TODO: link docs to argument keys
         // #[arg(is_help)]
         // help: plap::Arg<LitBool>,
@@ -222,16 +728,31 @@ plap::define_args!(
 
         #[arg(is_token_tree)]
         #[check(exclusive)]
-        remote: plap::Arg<Type>,
+        remote: plap::Arg<RemoteArgs>,
 
         #[arg(is_token_tree)]
         #[check(exclusive, conflicts_with_each = [prefix, suffix])]
         rename: plap::Arg<Ident>,
 
+        #[arg(is_token_tree)]
+        response: plap::Arg<ResponseArgs>,
+
+        #[arg(is_flag)]
+        #[check(exclusive)]
+        sealed: plap::Arg<LitBool>,
+
         #[arg(is_flag)]
         #[check(exclusive)]
         skip: plap::Arg<LitBool>,
 
+        #[arg(is_token_tree, optional)]
+        #[check(exclusive, conflicts_with_each = [default, flatten, magic, optional])]
+        source: plap::OptionalArg<SourceMode>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        status: plap::Arg<syn::LitInt>,
+
         #[arg(is_token_tree)]
         #[check(exclusive)]
         suffix: plap::Arg<Ident>,
@@ -243,3 +764,75 @@ plap::define_args!(
         visibility: plap::Arg<Visibility>,
     }
 );
+
+pub(crate) fn parse_into_error(input: &syn::DeriveInput) -> syn::Result<IntoErrorAttrs> {
+    let mut c = plap::Checker::default();
+    let mut args = <IntoErrorArgs as plap::Args>::init();
+    for (name, attr) in input
+        .attrs
+        .iter()
+        .flat_map(|a| a.path().get_ident().map(|i| (i, a)))
+    {
+        if name == "into_error" {
+            let r = attr.parse_args_with(|input: ParseStream| {
+                plap::Parser::new(input).parse_all(&mut args)
+            });
+            c.with_source(name.span());
+            c.with_result(r);
+        }
+    }
+    if args.target.is_empty() {
+        c.with_error_at(input.ident.span(), "`target` is required");
+    }
+    if args.source.is_empty() {
+        for key in args.source_field.keys() {
+            c.with_error_at(key.span(), format!("`{}` requires `source`", key));
+        }
+    }
+    plap::Args::check(&args, &mut c);
+    c.finish()?;
+    let IntoErrorArgs {
+        error,
+        source,
+        source_field,
+        target,
+    } = args;
+    Ok(IntoErrorAttrs {
+        error: error.take_last(),
+        source: source.take_last(),
+        source_field: source_field.take_last(),
+        // this has been checked above
+        target: target.take_last().unwrap_or_else(|| unreachable!()),
+    })
+}
+
+pub(crate) struct IntoErrorAttrs {
+    // defaults to the target without the variant, or the target itself
+    pub error: Option<Type>,
+    pub source: Option<Type>,
+    // the member of the target which the source is assigned to, defaults to
+    // `source`
+    pub source_field: Option<Ident>,
+    // #[into_error(target = <enum>::<variant>)] or #[into_error(target = <struct>)]
+    pub target: syn::Path,
+}
+
+plap::define_args!(
+    struct IntoErrorArgs {
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        error: plap::Arg<Type>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        source: plap::Arg<Type>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        source_field: plap::Arg<Ident>,
+
+        #[arg(is_token_tree)]
+        #[check(exclusive)]
+        target: plap::Arg<syn::Path>,
+    }
+);
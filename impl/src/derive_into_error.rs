@@ -0,0 +1,90 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::DeriveInput;
+
+use crate::derive_with_context::RT;
+use crate::util::QuoteWith;
+
+pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let attrs = crate::attrs::parse_into_error(&input)?;
+    let fields = match &input.data {
+        syn::Data::Struct(s) => &s.fields,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "only structs are supported",
+            ))
+        }
+    };
+    let target = &attrs.target;
+
+    // ErrorContext::Target
+    let error = match &attrs.error {
+        Some(error) => error.to_token_stream(),
+        None if target.segments.len() == 1 && target.leading_colon.is_none() => {
+            target.to_token_stream()
+        }
+        // `Error::Variant` targets a variant of the preceding type, while
+        // `module::Error` cannot be told apart from a variant in a module
+        None if is_type_name(&target.segments[target.segments.len() - 2].ident) => {
+            let mut error = target.clone();
+            let len = target.segments.len();
+            error.segments = target.segments.iter().take(len - 1).cloned().collect();
+            error.to_token_stream()
+        }
+        None => {
+            return Err(syn::Error::new_spanned(
+                target,
+                "`error` is required if `target` is a struct in a module, e.g. `error = \
+                 module::Error`",
+            ))
+        }
+    };
+
+    // ErrorContext::Source
+    let source = QuoteWith(|tokens| {
+        if let Some(source) = &attrs.source {
+            source.to_tokens(tokens);
+        } else {
+            tokens.extend(quote!(#RT::NoneSource));
+        }
+    });
+    let source_member = attrs.source.as_ref().map(|_| {
+        attrs
+            .source_field
+            .clone()
+            .unwrap_or_else(|| Ident::new("source", Span::call_site()))
+    });
+    let source_field = QuoteWith(|tokens| {
+        if let Some(member) = &source_member {
+            tokens.extend(quote!(#member: __source,));
+        }
+    });
+
+    let fields =
+        crate::derive_with_context::to_into_error_fields(&input, fields, source_member.as_ref())?;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote!(
+        impl #impl_generics #RT::ErrorContext for #name #ty_generics #where_clause {
+            type Target = #error;
+            type Source = #source;
+        }
+
+        impl #impl_generics #RT::IntoError for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn into_error(self, __source: #source) -> #error {
+                #target { #fields #source_field }
+            }
+        }
+    ))
+}
+
+// types are named in upper camel case, while modules are in snake case
+fn is_type_name(ident: &Ident) -> bool {
+    ident
+        .to_string()
+        .trim_start_matches("r#")
+        .starts_with(|c: char| c.is_ascii_uppercase())
+}
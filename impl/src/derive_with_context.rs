@@ -1,14 +1,15 @@
+use std::borrow::Cow;
 use std::collections::{btree_map, BTreeMap};
 use std::ops;
 
 use proc_macro2::{Delimiter, Group, Ident, Span, TokenStream};
-use quote::{format_ident, quote, ToTokens};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{DeriveInput, Field, Fields, GenericParam, Generics, Visibility};
 
-use crate::attrs::Attrs;
+use crate::attrs::{Attrs, CodeArg, MapArg, SourceMode, EX_SOFTWARE};
 use crate::util::QuoteWith;
 
-struct RT;
+pub(crate) struct RT;
 impl ToTokens for RT {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         // ::thisctx::private
@@ -19,6 +20,21 @@ impl ToTokens for RT {
     }
 }
 
+const RT_INTEGRATIONS: QuoteWith<fn(&mut TokenStream)> = QuoteWith(|tokens| {
+    // ::thisctx_integrations::private
+    NewToken![::].to_tokens(tokens);
+    NewIdent![thisctx_integrations].to_tokens(tokens);
+    NewToken![::].to_tokens(tokens);
+    NewIdent![private].to_tokens(tokens);
+});
+
+const RT_UNSET: QuoteWith<fn(&mut TokenStream)> = QuoteWith(|tokens| {
+    // ::thisctx::private::Unset
+    RT.to_tokens(tokens);
+    NewToken![::].to_tokens(tokens);
+    NewIdent![Unset].to_tokens(tokens);
+});
+
 // Some notes for reviewers:
 //
 // 1. Code generations are commented with eye-catching banners.
@@ -45,6 +61,49 @@ pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
         syn::Data::Enum(e) => {
             for variant in e.variants.iter() {
                 let v_attrs = crate::attrs::parse_variant(variant)?;
+                for group in v_attrs.group.iter() {
+                    if !input.generics.params.is_empty() {
+                        return Err(syn::Error::new(
+                            group.span(),
+                            "`group` is not supported on generic enums",
+                        ));
+                    }
+                    global
+                        .groups
+                        .entry(group.clone())
+                        .or_insert_with(Vec::new)
+                        .push(variant);
+                }
+                match &v_attrs.map {
+                    Some(MapArg::Skip) => {}
+                    map => global.mapped_variants.push(MappedVariant {
+                        source: match map {
+                            Some(MapArg::Variant(source)) => source.clone(),
+                            _ => variant.ident.clone(),
+                        },
+                        target: &variant.ident,
+                        fields: &variant.fields,
+                    }),
+                }
+                let mut context_fields = Vec::new();
+                for (i, field) in variant.fields.iter().enumerate() {
+                    let f_attrs = crate::attrs::parse_field(field)?;
+                    if !(v_attrs.transparent || f_attrs.source || f_attrs.backtrace) {
+                        context_fields.push((i, field));
+                    }
+                }
+                global.variants.push(VariantInfo {
+                    variant: &variant.ident,
+                    fields: &variant.fields,
+                    context_fields,
+                    code: v_attrs.code.clone(),
+                    exit_code: v_attrs.exit_code,
+                    grpc: v_attrs.grpc.clone(),
+                    // inherit #[thisctx(grpc_details)]
+                    grpc_details: v_attrs.grpc_details.or(attrs.grpc_details).unwrap_or(false),
+                    status: v_attrs.status.clone(),
+                    transparent: v_attrs.transparent,
+                });
                 // inherit #[thisctx(skip)]
                 if v_attrs.skip.or(attrs.skip).unwrap_or(false) {
                     continue;
@@ -68,10 +127,19 @@ pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
             ))
         }
     }
+    expand_getters(&input, &attrs, &mut global.methods)?;
+    expand_accessors(&input, &attrs, &mut global.output, &mut global.methods)?;
     let GlobalData {
         optional_fields,
+        from_impls,
+        mapped_variants,
+        groups,
+        variants,
+        mut methods,
         mut output,
     } = global;
+    // inherent methods and their types are as visible as the input
+    let input_vis = &input.vis;
 
     /* -------------------------- *
      * generate WithOptional impl *
@@ -113,20 +181,982 @@ pub(crate) fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
         ));
     }
 
+    /* ---------------------- *
+     * generate From<T> impls *
+     * ---------------------- */
+
+    // impl From<#ty> for #target
+    //           ^^^ variants are checked in order and the one without a
+    //               predicate is used as the fallback
+    for from_impl in from_impls.values() {
+        let FromImpl {
+            from_ty,
+            target,
+            branches,
+        } = from_impl;
+        let mut fallback = None::<&FromBranch>;
+        for branch in branches.iter().filter(|b| b.when.is_none()) {
+            if fallback.is_some() {
+                return Err(syn::Error::new(
+                    branch.span,
+                    format!(
+                        "conflicting `from` for `{}`, consider adding `when` to one of them",
+                        from_ty
+                    ),
+                ));
+            }
+            fallback = Some(branch);
+        }
+        let fallback = fallback.ok_or_else(|| {
+            syn::Error::new(
+                branches[0].span,
+                format!("`from` for `{}` requires a variant without `when`", from_ty),
+            )
+        })?;
+
+        let conditional_branches = QuoteWith(|tokens| {
+            for branch in branches.iter() {
+                if let Some(when) = &branch.when {
+                    let body = &branch.body;
+                    tokens.extend(quote!(
+                        if #RT::when(&__value, #when) {
+                            return #body;
+                        }
+                    ));
+                }
+            }
+        });
+        let fallback_body = &fallback.body;
+        let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+        output.extend(quote!(
+            #[allow(non_camel_case_types)]
+            impl #impl_generics #RT::From<#from_ty> for #target
+            #where_clause {
+                fn from(__value: #from_ty) -> #target {
+                    #conditional_branches
+                    #fallback_body
+                }
+            }
+        ));
+    }
+
+    /* ------------------------------ *
+     * generate From<#map_from> impls *
+     * ------------------------------ */
+
+    // impl From<#map_from> for #input
+    //           ^^^^^^^^^ variants are matched by their names, and unmapped
+    //                     ones result in non-exhaustive patterns
+    for map_from in attrs.map_from.iter() {
+        let input_name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        let match_arms = QuoteWith(|tokens| {
+            for MappedVariant {
+                source,
+                target,
+                fields,
+            } in mapped_variants.iter()
+            {
+                // Both sides can be constructed using members:
+                //
+                // Source::Variant { field: __0, 1: __1 } => Self::Variant {
+                //     field: Into::into(__0),
+                //     1: Into::into(__1),
+                // }
+                let pattern = QuoteWith(|tokens| {
+                    for (i, f) in fields.iter().enumerate() {
+                        let member = to_member(f, i);
+                        let binding = format_ident!("__{}", i);
+                        tokens.extend(quote!(#member: #binding,));
+                    }
+                });
+                let constructor = QuoteWith(|tokens| {
+                    for (i, f) in fields.iter().enumerate() {
+                        let member = to_member(f, i);
+                        let binding = format_ident!("__{}", i);
+                        let ty = &f.ty;
+                        tokens.extend(quote!(#member: #RT::Into::<#ty>::into(#binding),));
+                    }
+                });
+                tokens.extend(quote!(
+                    #map_from::#source { #pattern } => Self::#target { #constructor },
+                ));
+            }
+        });
+
+        output.extend(quote!(
+            impl #impl_generics #RT::From<#map_from> for #input_name #ty_generics #where_clause {
+                fn from(__value: #map_from) -> Self {
+                    match __value {
+                        #match_arms
+                    }
+                }
+            }
+        ));
+    }
+
     /* --------------------- *
      * generate final output *
      * --------------------- */
 
-    Ok(if let Some(module) = &attrs.module {
+    let mut output = if let Some(module) = &attrs.module {
         quote!(#vis mod #module { use super::*; #output })
     } else {
         output
-    })
+    };
+
+    /* ---------------------- *
+     * generate grouped enums *
+     * ---------------------- */
+
+    // Helper attributes of thiserror are kept only if the grouped enums
+    // derive `Error` as well.
+    let derives_error = attrs.group_attr.iter().any(is_derive_error);
+
+    // Grouped enums are placed outside of the module, next to the input.
+    for (group, variants) in groups.iter() {
+        let input_name = &input.ident;
+        let self_ty = input_name.to_token_stream();
+        let group_name = to_group_name(&input, group);
+        let group_attrs = QuoteWith(|tokens| {
+            for a in attrs.group_attr.iter() {
+                NewToken![#].to_tokens(tokens);
+                tokens.extend(Group::new(Delimiter::Bracket, a.clone()).into_token_stream());
+            }
+        });
+
+        // definition
+        let def_variants = QuoteWith(|tokens| {
+            for variant in variants.iter() {
+                // strip helper attributes, which are not recognized without
+                // `derive(WithContext)` or `derive(Error)`
+                let is_kept = |a: &syn::Attribute| {
+                    let path = a.path();
+                    !path.is_ident("thisctx")
+                        && (derives_error
+                            || !["error", "source", "from", "backtrace"]
+                                .iter()
+                                .any(|name| path.is_ident(name)))
+                };
+                let mut variant = (*variant).clone();
+                variant.attrs.retain(is_kept);
+                for f in variant.fields.iter_mut() {
+                    f.attrs.retain(is_kept);
+                    // `Self` refers to the input rather than the grouped enum
+                    let ty = crate::util::replace_self(f.ty.to_token_stream(), &self_ty);
+                    f.ty = syn::Type::Verbatim(ty);
+                }
+                variant.to_tokens(tokens);
+                NewToken![,].to_tokens(tokens);
+            }
+        });
+        output.extend(quote!(
+            #group_attrs
+            #input_vis enum #group_name {
+                #def_variants
+            }
+        ));
+
+        // conversions between the input and the grouped enum
+        let to_arms =
+            |from: &dyn ToTokens, to: &dyn ToTokens, wrap: &dyn Fn(TokenStream) -> TokenStream| {
+                let mut tokens = TokenStream::new();
+                for variant in variants.iter() {
+                    let variant_name = &variant.ident;
+                    // Group::Variant { field: __0, 1: __1 }
+                    let fields = QuoteWith(|tokens| {
+                        for (i, f) in variant.fields.iter().enumerate() {
+                            let member = to_member(f, i);
+                            let binding = format_ident!("__{}", i);
+                            tokens.extend(quote!(#member: #binding,));
+                        }
+                    });
+                    let body = wrap(quote!(#to::#variant_name { #fields }));
+                    tokens.extend(quote!(#from::#variant_name { #fields } => #body,));
+                }
+                tokens
+            };
+        let from_arms = to_arms(&group_name, &input_name, &|t| t);
+        let try_from_arms = to_arms(&input_name, &group_name, &|t| quote!(#RT::Ok(#t)));
+        output.extend(quote!(
+            impl #RT::From<#group_name> for #input_name {
+                fn from(__value: #group_name) -> Self {
+                    match __value {
+                        #from_arms
+                    }
+                }
+            }
+
+            impl #RT::TryFrom<#input_name> for #group_name {
+                type Error = #input_name;
+                #[allow(unreachable_patterns)]
+                fn try_from(__value: #input_name) -> #RT::Result<Self, #input_name> {
+                    match __value {
+                        #try_from_arms
+                        __value => #RT::Err(__value),
+                    }
+                }
+            }
+        ));
+    }
+
+    /* ---------------------- *
+     * generate the kind enum *
+     * ---------------------- */
+
+    if let (Some(kind), syn::Data::Enum(e)) = (&attrs.kind, &input.data) {
+        let kind_name = kind
+            .clone()
+            .unwrap_or_else(|| format_ident!("{}Kind", input.ident));
+        let kind_variants = QuoteWith(|tokens| {
+            for variant in e.variants.iter() {
+                variant.ident.to_tokens(tokens);
+                NewToken![,].to_tokens(tokens);
+            }
+        });
+        output.extend(quote!(
+            #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+            #input_vis enum #kind_name {
+                #kind_variants
+            }
+        ));
+
+        // Error::kind() and Error::variant_name()
+        let kind_arms = QuoteWith(|tokens| {
+            for variant in e.variants.iter() {
+                let variant = &variant.ident;
+                tokens.extend(quote!(Self::#variant { .. } => #kind_name::#variant,));
+            }
+        });
+        let name_arms = QuoteWith(|tokens| {
+            for variant in e.variants.iter() {
+                let variant = &variant.ident;
+                // stable names without the `r#` prefix
+                let name = variant.to_string();
+                let name = name.trim_start_matches("r#");
+                tokens.extend(quote!(#kind_name::#variant => #name,));
+            }
+        });
+        output.extend(quote!(
+            impl #kind_name {
+                #input_vis fn variant_name(self) -> &'static str {
+                    match self {
+                        #name_arms
+                    }
+                }
+            }
+        ));
+        methods.push(
+            &NewIdent![kind],
+            quote!(
+                #input_vis fn kind(&self) -> #kind_name {
+                    match *self {
+                        #kind_arms
+                    }
+                }
+            ),
+        )?;
+        methods.push(
+            &NewIdent![variant_name],
+            quote!(
+                #input_vis fn variant_name(&self) -> &'static str {
+                    self.kind().variant_name()
+                }
+            ),
+        )?;
+    }
+
+    /* ---------------------- *
+     * generate variant codes *
+     * ---------------------- */
+
+    if variants.iter().any(|c| c.code.is_some()) {
+        // check missing and duplicate codes
+        let mut seen = BTreeMap::<String, &Ident>::new();
+        let mut missing = Vec::new();
+        for c in variants.iter() {
+            match &c.code {
+                Some(code) => {
+                    if let Some(prev) = seen.insert(code.value()?, c.variant) {
+                        return Err(syn::Error::new(
+                            code.span(),
+                            format!("duplicate code of `{}` and `{}`", prev, c.variant),
+                        ));
+                    }
+                }
+                // transparent variants delegate to the inner error
+                None if c.transparent => {}
+                None => missing.push(format!("`{}`", c.variant)),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                format!("`code` is missing in variants: {}", missing.join(", ")),
+            ));
+        }
+
+        let to_code = |code: &CodeArg| match code {
+            CodeArg::Number(n) => quote!(#RT::ErrorCode::Number(#n)),
+            CodeArg::Name(s) => quote!(#RT::ErrorCode::Name(#s)),
+        };
+        let code_arms = QuoteWith(|tokens| {
+            for c in variants.iter() {
+                let variant = c.variant;
+                if let Some(code) = &c.code {
+                    let code = to_code(code);
+                    tokens.extend(quote!(Self::#variant { .. } => #code,));
+                } else {
+                    let pat = c.to_transparent_pattern();
+                    tokens.extend(quote!(#pat => __inner.code(),));
+                }
+            }
+        });
+        let code_table = QuoteWith(|tokens| {
+            for c in variants.iter() {
+                if let Some(code) = &c.code {
+                    let code = to_code(code);
+                    let name = c.variant.to_string();
+                    let name = name.trim_start_matches("r#");
+                    tokens.extend(quote!((#code, #name),));
+                }
+            }
+        });
+        methods.push(
+            &NewIdent![CODES],
+            quote!(
+                /// Codes of all variants, except the transparent ones.
+                #input_vis const CODES: &'static [(#RT::ErrorCode, &'static str)] = &[#code_table];
+            ),
+        )?;
+        methods.push(
+            &NewIdent![code],
+            quote!(
+                #input_vis fn code(&self) -> #RT::ErrorCode {
+                    match *self {
+                        #code_arms
+                    }
+                }
+            ),
+        )?;
+        methods.push(
+            &NewIdent![variant_name_of_code],
+            quote!(
+                /// Returns the name of the variant with the specified code.
+                #input_vis fn variant_name_of_code(
+                    code: #RT::ErrorCode,
+                ) -> #RT::Option<&'static str> {
+                    Self::CODES
+                        .iter()
+                        .find(|(c, _)| *c == code)
+                        .map(|(_, name)| *name)
+                }
+            ),
+        )?;
+    }
+
+    /* ------------------------- *
+     * generate variant statuses *
+     * ------------------------- */
+
+    let has_status = attrs.status.is_some() || variants.iter().any(|v| v.status.is_some());
+    let response = attrs.response;
+    if (response.axum || response.actix) && !has_status {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "`response` requires `status`",
+        ));
+    }
+    if has_status {
+        let to_status = |status: &syn::LitInt| -> syn::Result<u16> {
+            match status.base10_parse::<u16>() {
+                Ok(n) if (100..1000).contains(&n) => Ok(n),
+                _ => Err(syn::Error::new(
+                    status.span(),
+                    "expected a status code between 100 and 999",
+                )),
+            }
+        };
+        let default = attrs.status.as_ref().map(to_status).transpose()?;
+        let mut status_arms = TokenStream::new();
+        let mut missing = Vec::new();
+        for v in variants.iter() {
+            let variant = v.variant;
+            match &v.status {
+                Some(status) => {
+                    let status = to_status(status)?;
+                    status_arms.extend(quote!(Self::#variant { .. } => #status,));
+                }
+                // transparent variants delegate to the inner error
+                None if v.transparent => {
+                    let pat = v.to_transparent_pattern();
+                    status_arms.extend(quote!(#pat => return __inner.status(),));
+                }
+                // inherit #[thisctx(status)]
+                None => match default {
+                    Some(status) => status_arms.extend(quote!(Self::#variant { .. } => #status,)),
+                    None => missing.push(format!("`{}`", variant)),
+                },
+            }
+        }
+        if !missing.is_empty() {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                format!("`status` is missing in variants: {}", missing.join(", ")),
+            ));
+        }
+        methods.push(
+            &NewIdent![status],
+            quote!(
+                #input_vis fn status(&self) -> #RT_INTEGRATIONS::http::StatusCode {
+                    #RT_INTEGRATIONS::status_code(match *self {
+                        #status_arms
+                    })
+                }
+            ),
+        )?;
+
+        if response.axum || response.actix {
+            // renders context fields as extension members of the problem details
+            let problem_arms = QuoteWith(|tokens| {
+                for v in variants.iter() {
+                    let variant = v.variant;
+                    let pattern = v.to_context_pattern();
+                    let extensions = v.to_context_values();
+                    tokens.extend(quote!(
+                        Self::#variant { #pattern .. } => #RT_INTEGRATIONS::problem_details(
+                            __status.as_u16(),
+                            __status.canonical_reason(),
+                            self,
+                            [#extensions],
+                        ),
+                    ));
+                }
+            });
+            methods.push(
+                &NewIdent![__problem_details],
+                quote!(
+                    #[doc(hidden)]
+                    fn __problem_details(&self) -> #RT_INTEGRATIONS::Vec<u8> {
+                        let __status = self.status();
+                        match *self {
+                            #problem_arms
+                        }
+                    }
+                ),
+            )?;
+
+            let input_name = &input.ident;
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            if response.axum {
+                output.extend(quote!(
+                    impl #impl_generics #RT_INTEGRATIONS::axum_core::response::IntoResponse
+                        for #input_name #ty_generics #where_clause
+                    {
+                        fn into_response(self) -> #RT_INTEGRATIONS::axum_core::response::Response {
+                            #RT_INTEGRATIONS::axum_response(self.status(), self.__problem_details())
+                        }
+                    }
+                ));
+            }
+            if response.actix {
+                output.extend(quote!(
+                    impl #impl_generics #RT_INTEGRATIONS::actix_web::ResponseError
+                        for #input_name #ty_generics #where_clause
+                    {
+                        fn status_code(&self) -> #RT_INTEGRATIONS::actix_web::http::StatusCode {
+                            #RT_INTEGRATIONS::actix_status(self.status())
+                        }
+
+                        fn error_response(&self) -> #RT_INTEGRATIONS::actix_web::HttpResponse {
+                            let status = self.status();
+                            #RT_INTEGRATIONS::actix_response(status, self.__problem_details())
+                        }
+                    }
+                ));
+            }
+        }
+    }
+
+    /* -------------------------- *
+     * generate gRPC status codes *
+     * -------------------------- */
+
+    if attrs.grpc.is_some() || variants.iter().any(|v| v.grpc.is_some()) {
+        let mut code_arms = TokenStream::new();
+        let mut status_arms = TokenStream::new();
+        let mut missing = Vec::new();
+        for v in variants.iter() {
+            let variant = v.variant;
+            if v.grpc_details {
+                let pattern = v.to_context_pattern();
+                let details = v.to_context_values();
+                status_arms.extend(quote!(
+                    Self::#variant { #pattern .. } => {
+                        #RT_INTEGRATIONS::grpc_status_with_details(__code, self, [#details])
+                    }
+                ));
+            } else {
+                status_arms.extend(quote!(
+                    Self::#variant { .. } => #RT_INTEGRATIONS::grpc_status(__code, self),
+                ));
+            }
+            match &v.grpc {
+                Some(code) => {
+                    code_arms.extend(quote!(
+                        Self::#variant { .. } => #RT_INTEGRATIONS::tonic::Code::#code,
+                    ));
+                }
+                // transparent variants delegate to the inner error
+                None if v.transparent => {
+                    let pat = v.to_transparent_pattern();
+                    code_arms.extend(quote!(#pat => __inner.grpc_code(),));
+                }
+                // inherit #[thisctx(grpc)]
+                None => match &attrs.grpc {
+                    Some(code) => code_arms.extend(quote!(
+                        Self::#variant { .. } => #RT_INTEGRATIONS::tonic::Code::#code,
+                    )),
+                    None => missing.push(format!("`{}`", variant)),
+                },
+            }
+        }
+        if !missing.is_empty() {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                format!("`grpc` is missing in variants: {}", missing.join(", ")),
+            ));
+        }
+        methods.push(
+            &NewIdent![grpc_code],
+            quote!(
+                #input_vis fn grpc_code(&self) -> #RT_INTEGRATIONS::tonic::Code {
+                    match *self {
+                        #code_arms
+                    }
+                }
+            ),
+        )?;
+        methods.push(
+            &NewIdent![__grpc_status],
+            quote!(
+                #[doc(hidden)]
+                fn __grpc_status(&self) -> #RT_INTEGRATIONS::tonic::Status {
+                    let __code = self.grpc_code();
+                    match *self {
+                        #status_arms
+                    }
+                }
+            ),
+        )?;
+
+        let input_name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        output.extend(quote!(
+            impl #impl_generics #RT::From<#input_name #ty_generics>
+                for #RT_INTEGRATIONS::tonic::Status
+            #where_clause
+            {
+                fn from(error: #input_name #ty_generics) -> Self {
+                    error.__grpc_status()
+                }
+            }
+        ));
+    }
+
+    /* --------------------------- *
+     * generate process exit codes *
+     * --------------------------- */
+
+    if attrs.exit_code.is_some() || variants.iter().any(|v| v.exit_code.is_some()) {
+        let mut exit_code_arms = TokenStream::new();
+        for v in variants.iter() {
+            let variant = v.variant;
+            match v.exit_code {
+                Some(code) => {
+                    exit_code_arms.extend(quote!(Self::#variant { .. } => #code,));
+                }
+                // transparent variants delegate to the inner error
+                None if v.transparent => {
+                    let pat = v.to_transparent_pattern();
+                    exit_code_arms.extend(quote!(#pat => return __inner.exit_code(),));
+                }
+                // inherit #[thisctx(exit_code)], or fall back to EX_SOFTWARE
+                None => {
+                    let code = attrs.exit_code.unwrap_or(EX_SOFTWARE);
+                    exit_code_arms.extend(quote!(Self::#variant { .. } => #code,));
+                }
+            }
+        }
+        methods.push(
+            &NewIdent![exit_code],
+            quote!(
+                #input_vis fn exit_code(&self) -> #RT::ExitCode {
+                    #RT::From::from(match *self {
+                        #exit_code_arms
+                    })
+                }
+            ),
+        )?;
+
+        let input_name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+        output.extend(quote!(
+            impl #impl_generics #RT::Termination for #input_name #ty_generics
+            #where_clause
+            {
+                fn report(self) -> #RT::ExitCode {
+                    #RT::eprintln!("Error: {}", self);
+                    self.exit_code()
+                }
+            }
+        ));
+    }
+
+    // Inherent methods are placed outside of the module, so that their
+    // visibilities are the same as the input.
+    if !methods.tokens.is_empty() {
+        let methods = methods.tokens;
+        let input_name = &input.ident;
+        let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+        output.extend(quote!(
+            impl #impl_generics #input_name #ty_generics #where_clause {
+                #methods
+            }
+        ));
+    }
+    Ok(output)
+}
+
+fn expand_accessors(
+    input: &DeriveInput,
+    attrs: &Attrs,
+    output: &mut TokenStream,
+    methods: &mut Methods,
+) -> syn::Result<()> {
+    /* -------------------------- *
+     * generate variant accessors *
+     * -------------------------- */
+
+    let variants = match &input.data {
+        syn::Data::Enum(e) => &e.variants,
+        _ => return Ok(()),
+    };
+    let input_name = &input.ident;
+    let input_vis = &input.vis;
+    let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+    let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+    // `Self` in the references refers to the input
+    let self_ty = quote!(#input_name #ty_generics);
+    // references of generic enums capture all parameters, since each one
+    // borrows only the fields of its variant
+    let marker = to_phantom_marker(&input.generics);
+    let has_marker = input
+        .generics
+        .params
+        .iter()
+        .any(|p| !matches!(p, GenericParam::Const(_)));
+
+    // Skipped variants have no contexts, but they still get accessors.
+    for variant in variants.iter() {
+        let v_attrs = crate::attrs::parse_variant(variant)?;
+        // inherit #[thisctx(accessors)]
+        if !v_attrs.accessors.or(attrs.accessors).unwrap_or(false) {
+            continue;
+        }
+        let variant_name = &variant.ident;
+        let fields = &variant.fields;
+        let snake = crate::util::to_snake_case(variant_name).to_string();
+        let snake = snake.trim_start_matches("r#");
+        let is_fn = format_ident!("is_{}", snake, span = variant_name.span());
+        let as_fn = format_ident!("as_{}", snake, span = variant_name.span());
+        let source_of_fn = format_ident!("source_of_{}", snake, span = variant_name.span());
+
+        // Error::is_my_variant()
+        methods.push(
+            &is_fn,
+            quote!(
+                #input_vis fn #is_fn(&self) -> bool {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        Self::#variant_name { .. } => true,
+                        _ => false,
+                    }
+                }
+            ),
+        )?;
+
+        // Error::source_of_my_variant()
+        let mut source_field = None;
+        for (i, field) in fields.iter().enumerate() {
+            let f_attrs = crate::attrs::parse_field(field)?;
+            if v_attrs.transparent || f_attrs.source || f_attrs.from.is_some() {
+                source_field = Some((i, field));
+                break;
+            }
+        }
+        let source_field = source_field.or_else(|| {
+            fields
+                .iter()
+                .enumerate()
+                .find(|(_, f)| f.ident.as_ref().map_or(false, |i| i == "source"))
+        });
+        if let Some((i, field)) = source_field {
+            let member = to_member(field, i);
+            let ty = &field.ty;
+            methods.push(
+                &source_of_fn,
+                quote!(
+                    #input_vis fn #source_of_fn(&self) -> #RT::Option<&#ty> {
+                        #[allow(unreachable_patterns)]
+                        match self {
+                            Self::#variant_name { #member: __source, .. } => {
+                                #RT::Option::Some(__source)
+                            }
+                            _ => #RT::Option::None,
+                        }
+                    }
+                ),
+            )?;
+        }
+
+        // Error::as_my_variant() -> Option<MyVariantRef>
+        if fields.is_empty() {
+            continue;
+        }
+        let name = v_attrs
+            .rename(variant_name)
+            // inherit #[thisctx(prefix)]
+            // inherit #[thisctx(suffix)]
+            .or_else(|| attrs.rename(variant_name))
+            .unwrap_or_else(|| variant_name.clone());
+        let ref_name = format_ident!("{}Ref", name, span = name.span());
+        let ref_fields = QuoteWith(|tokens| {
+            for f in fields.iter() {
+                input_vis.to_tokens(tokens);
+                f.ident.to_tokens(tokens);
+                f.colon_token.to_tokens(tokens);
+                let ty = crate::util::replace_self(f.ty.to_token_stream(), &self_ty);
+                tokens.extend(quote!(&'__a #ty,));
+            }
+            if has_marker {
+                input_vis.to_tokens(tokens);
+                if let Fields::Named(_) = fields {
+                    tokens.extend(quote!(__marker:));
+                }
+                tokens.extend(quote!(#RT::PhantomData<fn() -> (#marker)>,));
+            }
+        });
+        let ref_def = match fields {
+            Fields::Named(_) => quote!(#where_clause { #ref_fields }),
+            _ => quote!((#ref_fields) #where_clause;),
+        };
+        output.extend(quote!(
+            #[allow(non_camel_case_types)]
+            #input_vis struct #ref_name<'__a, #impl_params #impl_kst_params> #ref_def
+        ));
+
+        // the reference is placed inside the module
+        let ref_path = QuoteWith(|tokens| {
+            if let Some(module) = &attrs.module {
+                module.to_tokens(tokens);
+                NewToken![::].to_tokens(tokens);
+            }
+            ref_name.to_tokens(tokens);
+        });
+        // MyVariantRef { field: __0, 1: __1 }
+        let ref_members = QuoteWith(|tokens| {
+            for (i, f) in fields.iter().enumerate() {
+                let member = to_member(f, i);
+                let binding = format_ident!("__{}", i);
+                tokens.extend(quote!(#member: #binding,));
+            }
+        });
+        let ref_marker = QuoteWith(|tokens| {
+            if has_marker {
+                if let Fields::Named(_) = fields {
+                    tokens.extend(quote!(__marker: #RT::PhantomData,));
+                } else {
+                    let index = syn::Index::from(fields.len());
+                    tokens.extend(quote!(#index: #RT::PhantomData,));
+                }
+            }
+        });
+        methods.push(
+            &as_fn,
+            quote!(
+                #input_vis fn #as_fn(
+                    &self,
+                ) -> #RT::Option<#ref_path<'_, #ty_params #ty_kst_params>> {
+                    #[allow(unreachable_patterns)]
+                    match self {
+                        Self::#variant_name { #ref_members } => {
+                            #RT::Option::Some(#ref_path { #ref_members #ref_marker })
+                        }
+                        _ => #RT::Option::None,
+                    }
+                }
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+fn expand_getters(input: &DeriveInput, attrs: &Attrs, methods: &mut Methods) -> syn::Result<()> {
+    /* ---------------------- *
+     * generate field getters *
+     * ---------------------- */
+
+    let variants = match &input.data {
+        syn::Data::Struct(s) => vec![(None, &s.fields)],
+        syn::Data::Enum(e) => e
+            .variants
+            .iter()
+            .map(|v| (Some(&v.ident), &v.fields))
+            .collect(),
+        syn::Data::Union(_) => return Ok(()),
+    };
+
+    // collect fields marked with #[thisctx(getter)]
+    let mut getters = Vec::<(&Field, Option<Visibility>)>::new();
+    for (_, fields) in variants.iter() {
+        for field in fields.iter() {
+            let f_attrs = crate::attrs::parse_field(field)?;
+            if f_attrs.getter == Some(true) && !getters.iter().any(|(f, _)| f.ident == field.ident)
+            {
+                getters.push((field, f_attrs.vis));
+            }
+        }
+    }
+    // #[thisctx(getter)] on enums detects fields shared by all variants
+    if attrs.getter.unwrap_or(false) {
+        if let Some((_, fields)) = variants.first() {
+            for field in fields.iter().filter(|f| f.ident.is_some()) {
+                // sources, backtraces and optional fields are not detected,
+                // e.g. `source()` would shadow `Error::source`
+                let f_attrs = crate::attrs::parse_field(field)?;
+                if f_attrs.is_excluded() || field.ident.as_ref().map_or(false, |i| i == "source") {
+                    continue;
+                }
+                let ty = field.ty.to_token_stream().to_string();
+                let shared = variants.iter().all(|(_, fields)| {
+                    fields
+                        .iter()
+                        .any(|f| f.ident == field.ident && f.ty.to_token_stream().to_string() == ty)
+                });
+                if shared && !getters.iter().any(|(f, _)| f.ident == field.ident) {
+                    getters.push((field, None));
+                }
+            }
+        }
+    }
+
+    for (field, field_vis) in getters {
+        let ident = field.ident.as_ref().unwrap_or_else(|| unreachable!());
+        let ty = &field.ty;
+        let ty_str = ty.to_token_stream().to_string();
+
+        let mut missing = Vec::new();
+        let mut mismatched = Vec::new();
+        for (variant, fields) in variants.iter() {
+            match fields.iter().find(|f| f.ident.as_ref() == Some(ident)) {
+                None => missing.extend(*variant),
+                Some(f) if f.ty.to_token_stream().to_string() != ty_str => {
+                    mismatched.extend(*variant)
+                }
+                _ => {}
+            }
+        }
+        let join = |variants: &[&Ident]| {
+            variants
+                .iter()
+                .map(|v| format!("`{}`", v))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        if !missing.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!("`{}` is missing in variants: {}", ident, join(&missing)),
+            ));
+        }
+        if !mismatched.is_empty() {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "`{}` has different types in variants: {}",
+                    ident,
+                    join(&mismatched)
+                ),
+            ));
+        }
+
+        let vis = field_vis.as_ref().unwrap_or(&input.vis);
+        let body = QuoteWith(|tokens| {
+            if matches!(input.data, syn::Data::Struct(_)) {
+                tokens.extend(quote!(&self.#ident));
+            } else {
+                let arms = QuoteWith(|tokens| {
+                    for (variant, _) in variants.iter() {
+                        tokens.extend(quote!(Self::#variant { #ident, .. } => #ident,));
+                    }
+                });
+                tokens.extend(quote!(match self { #arms }));
+            }
+        });
+        methods.push(
+            ident,
+            quote!(
+                #vis fn #ident(&self) -> &#ty {
+                    #body
+                }
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Methods {
+    names: BTreeMap<String, Span>,
+    tokens: TokenStream,
+}
+
+impl Methods {
+    /// Adds an inherent method and rejects a name used by another one, e.g. a
+    /// constructor of the variant `Kind` and the method generated by
+    /// `#[thisctx(kind)]`.
+    fn push(&mut self, name: &Ident, method: TokenStream) -> syn::Result<()> {
+        let key = name.to_string();
+        let key = key.trim_start_matches("r#");
+        // the previous one is more likely to be spanned to user code
+        if let Some(prev) = self.names.insert(key.to_owned(), name.span()) {
+            return Err(syn::Error::new(
+                prev,
+                format!("`{}` conflicts with another generated method", key),
+            ));
+        }
+        self.tokens.extend(method);
+        Ok(())
+    }
 }
 
 #[derive(Default)]
 struct GlobalData<'a> {
     optional_fields: BTreeMap<Ident, Vec<OptionalField<'a>>>,
+    from_impls: BTreeMap<String, FromImpl>,
+    // variants converted from #[thisctx(map_from)]
+    mapped_variants: Vec<MappedVariant<'a>>,
+    // variants of each #[thisctx(group)]
+    groups: BTreeMap<Ident, Vec<&'a syn::Variant>>,
+    // all variants of the input enum
+    variants: Vec<VariantInfo<'a>>,
+    // methods of the input type
+    methods: Methods,
     output: TokenStream,
 }
 
@@ -136,6 +1166,71 @@ struct OptionalField<'a> {
     index: usize,
 }
 
+struct VariantInfo<'a> {
+    variant: &'a Ident,
+    fields: &'a Fields,
+    // fields excluding sources and backtraces
+    context_fields: Vec<(usize, &'a Field)>,
+    code: Option<CodeArg>,
+    exit_code: Option<u8>,
+    grpc: Option<Ident>,
+    grpc_details: bool,
+    status: Option<syn::LitInt>,
+    transparent: bool,
+}
+
+impl VariantInfo<'_> {
+    // matches the inner error of a transparent variant
+    fn to_transparent_pattern(&self) -> TokenStream {
+        let variant = self.variant;
+        let member = self.fields.iter().next().map(|f| to_member(f, 0));
+        quote!(Self::#variant { #member: ref __inner, .. })
+    }
+
+    // binds context fields to `__field<index>`
+    fn to_context_pattern(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        for (i, f) in self.context_fields.iter() {
+            let member = to_member(f, *i);
+            let binding = format_ident!("__field{}", i);
+            tokens.extend(quote!(#member: ref #binding,));
+        }
+        tokens
+    }
+
+    // (<name>, <value>) pairs of context fields rendered as JSON
+    fn to_context_values(&self) -> TokenStream {
+        let mut tokens = TokenStream::new();
+        for (i, f) in self.context_fields.iter() {
+            let name = match &f.ident {
+                Some(ident) => ident.to_string().trim_start_matches("r#").to_owned(),
+                None => i.to_string(),
+            };
+            let binding = format_ident!("__field{}", i);
+            tokens.extend(quote!((#name, #RT_INTEGRATIONS::serde_json::to_value(#binding)),));
+        }
+        tokens
+    }
+}
+
+struct MappedVariant<'a> {
+    source: Ident,
+    target: &'a Ident,
+    fields: &'a Fields,
+}
+
+struct FromImpl {
+    from_ty: TokenStream,
+    target: TokenStream,
+    branches: Vec<FromBranch>,
+}
+
+struct FromBranch {
+    span: Span,
+    when: Option<TokenStream>,
+    body: TokenStream,
+}
+
 struct ContextInfo<'i, 'a> {
     input: &'i DeriveInput,
     name: &'i Ident,
@@ -155,6 +1250,7 @@ impl<'i> ContextInfo<'i, '_> {
             attrs,
             vis,
         } = self;
+        let input_vis = &input.vis;
         let fields_info = self.parse_fields_info(global)?;
 
         /* ------------------------ *
@@ -172,15 +1268,24 @@ impl<'i> ContextInfo<'i, '_> {
             return Err(self.error(format!("name conflicts: `{}`", input.ident)));
         }
 
-        // IntoError::Target
+        // ErrorContext::Target
+        let mirror = parent_attrs.and_then(|a| a.mirror.as_ref());
         let remote = attrs
             .remote
             .as_ref()
             // inherit #[thisctx(remote)]
-            .or_else(|| parent_attrs.and_then(|a| a.remote.as_ref()));
+            .or_else(|| parent_attrs.and_then(|a| a.remote.as_ref()))
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        if mirror.is_some() && !remote.is_empty() {
+            return Err(self.error("`remote` conflicts with `mirror`"));
+        }
         let target = QuoteWith(|tokens| {
-            if let Some(remote) = remote {
-                // change IntoError::Target to the specified remote type
+            if let Some(mirror) = mirror {
+                // the mirrored enum is the real target
+                mirror.to_tokens(tokens);
+            } else if let Some(remote) = remote.first() {
+                // change ErrorContext::Target to the specified remote type
                 remote.to_tokens(tokens);
             } else {
                 input.ident.to_tokens(tokens);
@@ -188,16 +1293,48 @@ impl<'i> ContextInfo<'i, '_> {
             }
         });
 
-        // IntoError::Source
+        // ErrorContext::Source
         let source = QuoteWith(|tokens| {
             if let Some(i) = fields_info.source_field {
                 // use the specified source type
-                fields_info[i].ty.to_tokens(tokens);
+                fields_info[i].to_source_ty().to_tokens(tokens);
             } else {
                 tokens.extend(quote!(#RT::NoneSource));
             }
         });
 
+        // IntoError<__S> for each `__S: Into<Source>`
+        let generic_source = fields_info.source_field.map_or(false, |i| {
+            fields_info[i].attrs.source_mode == Some(SourceMode::Generic)
+        });
+        let source_param = QuoteWith(|tokens| {
+            if generic_source {
+                tokens.extend(quote!(__S,));
+            }
+        });
+        let source_arg = QuoteWith(|tokens| {
+            if generic_source {
+                tokens.extend(quote!(<__S>));
+            }
+        });
+        let source_arg_ty = QuoteWith(|tokens| {
+            if generic_source {
+                tokens.extend(quote!(__S));
+            } else {
+                source.to_tokens(tokens);
+            }
+        });
+        let source_bound = QuoteWith(|tokens| {
+            if generic_source {
+                tokens.extend(quote!(__S: #RT::Into<#source>,));
+            }
+        });
+        let source_conversion = QuoteWith(|tokens| {
+            if generic_source {
+                tokens.extend(quote!(let __source = #RT::Into::<#source>::into(__source);));
+            }
+        });
+
         // magic generics
         let magic_params = fields_info.to_generic_params(false);
         let magic_bounds = fields_info.to_generic_bounds();
@@ -217,7 +1354,10 @@ impl<'i> ContextInfo<'i, '_> {
                 // > rustc: generic parameters with a default must be trailing
                 // > using type defaults and const parameters in the same parameter
                 // > list is currently not permitted
-                input.generics.const_params().count() == 0,
+                //
+                // or fields of flattened structs, whose types are unknown.
+                input.generics.const_params().count() == 0
+                    && fields_info.iter().all(|f| f.nested_in.is_none()),
             );
             let (def_params, def_kst_params) = split_generic_params(&input.generics, true, true);
 
@@ -252,36 +1392,374 @@ impl<'i> ContextInfo<'i, '_> {
                     NewToken![;].to_tokens(tokens);
                 }
             });
-
+
+            let sealed_attrs = QuoteWith(|tokens| {
+                if fields_info.sealed {
+                    // new fields can be added without breaking changes
+                    tokens.extend(quote!(#[non_exhaustive]));
+                }
+            });
+
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)] #outer_attrs #sealed_attrs
+                #vis struct #name<#def_params #magic_defs #def_kst_params>
+                #def_body_with_where_clause
+            ));
+        }
+
+        /* -------------------------- *
+         * generate constructor impls *
+         * -------------------------- */
+
+        if fields_info.sealed {
+            let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+            let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+            let generic_bounds = to_generic_bounds(&input.generics);
+
+            let new_args = fields_info.to_args();
+            let new_body = fields_info.to_self_constructor();
+
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params>
+                #name<#ty_params #magic_params #ty_kst_params>
+                where #generic_bounds #magic_bounds {
+                    #[allow(clippy::new_without_default, clippy::too_many_arguments)]
+                    #vis fn new(#new_args) -> Self {
+                        #new_body
+                    }
+                }
+            ));
+        }
+
+        /* ----------------------- *
+         * generate IntoError impl *
+         * ----------------------- */
+        {
+            let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+            let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+            let geneirc_bounds = to_generic_bounds(&input.generics);
+
+            let variant_prefix = to_variant_prefix(input, mirror);
+            let into_error_body =
+                to_constructor(input, &fields_info, "__source", context_from_self);
+
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params> #RT::ErrorContext
+                for #name<#ty_params #magic_params #ty_kst_params>
+                where #geneirc_bounds #magic_bounds {
+                    type Target = #target;
+                    type Source = #source;
+                }
+
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #source_param #impl_kst_params>
+                #RT::IntoError #source_arg
+                for #name<#ty_params #magic_params #ty_kst_params>
+                where #geneirc_bounds #magic_bounds #source_bound {
+                    fn into_error(self, __source: #source_arg_ty) -> #target {
+                        #source_conversion
+                        #RT::Into::<#target>::into(
+                            #variant_prefix #orig_name #into_error_body
+                        )
+                    }
+                }
+            ));
+        }
+
+        /* -------------------------------- *
+         * generate IntoError<Unboxed> impl *
+         * -------------------------------- */
+
+        // impl IntoError<#input> for #context
+        //
+        // `Box<Self>` sources also accept the unboxed error.
+        if let Some(unboxed) = fields_info
+            .source_field
+            .and_then(|i| fields_info[i].unboxed_ty.as_ref())
+        {
+            let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+            let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+            let generic_bounds = to_generic_bounds(&input.generics);
+
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params> #RT::IntoError<#unboxed>
+                for #name<#ty_params #magic_params #ty_kst_params>
+                where #generic_bounds #magic_bounds {
+                    fn into_error(self, __source: #unboxed) -> #target {
+                        #RT::IntoError::<#source>::into_error(self, #RT::From::from(__source))
+                    }
+                }
+            ));
+        }
+
+        /* --------------------- *
+         * generate Flatten impl *
+         * --------------------- */
+
+        // impl Flatten<(#field_ty1, #field_ty2,)> for #input
+        //
+        // Structs without a source can be flattened into other contexts.
+        if parent_attrs.is_none() && remote.is_empty() && fields_info.source_field.is_none() {
+            let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+            let generic_bounds = to_generic_bounds(&input.generics);
+            let input_name = &input.ident;
+            let input_vis = &input.vis;
+            let (input_impl_generics, input_ty_generics, input_where_clause) =
+                input.generics.split_for_impl();
+
+            // Values are passed by position, the index of each field lets
+            // `#[thisctx(flatten(<fields>))]` check the names and their order.
+            let field_indices = QuoteWith(|tokens| {
+                for (i, f) in fields_info.context_fields() {
+                    if let Some(ident) = f.context_ident() {
+                        let index_const = format_ident!("__flatten_{}", ident);
+                        tokens.extend(quote!(
+                            #[doc(hidden)]
+                            #[allow(dead_code, non_upper_case_globals)]
+                            #input_vis const #index_const: #RT::FlattenIndex<#i> =
+                                #RT::FlattenIndex;
+                        ));
+                    }
+                }
+            });
+
+            let field_tys = QuoteWith(|tokens| {
+                for (_, f) in fields_info.context_fields() {
+                    f.to_def_ty().to_tokens(tokens);
+                    NewToken![,].to_tokens(tokens);
+                }
+            });
+            let field_args = QuoteWith(|tokens| {
+                for (i, f) in fields_info.context_fields() {
+                    f.to_arg_ident(i).to_tokens(tokens);
+                    NewToken![,].to_tokens(tokens);
+                }
+            });
+            let ctx_fields = QuoteWith(|tokens| {
+                for (i, f) in fields_info.context_fields() {
+                    let member = f.to_context_member(i);
+                    let arg = f.to_arg_ident(i);
+                    tokens.extend(quote!(#member: #arg,));
+                }
+            });
             global.output.extend(quote!(
-                #[allow(non_camel_case_types)] #outer_attrs
-                #vis struct #name<#def_params #magic_defs #def_kst_params>
-                #def_body_with_where_clause
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params> #RT::Flatten<(#field_tys)>
+                for #input_name #input_ty_generics
+                where #generic_bounds #magic_bounds {
+                    fn flatten((#field_args): (#field_tys)) -> Self {
+                        #RT::IntoError::build(#name { #ctx_fields })
+                    }
+                }
+
+                impl #input_impl_generics #input_name #input_ty_generics #input_where_clause {
+                    #field_indices
+                }
             ));
         }
 
-        /* ----------------------- *
-         * generate IntoError impl *
-         * ----------------------- */
-        {
+        /* --------------------------- *
+         * generate IntoErrorFor impls *
+         * --------------------------- */
+
+        // impl IntoErrorFor<#remote> for #context
+        //                   ^^^^^^^ each of the remote types except the first
+        //                           one, which is the target, and the groups
+        //                           of the variant
+        //
+        // `IntoErrorFor<Target>` is implemented for all contexts.
+        if remote.len() > 1 || !attrs.group.is_empty() {
             let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
             let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
-            let geneirc_bounds = to_generic_bounds(&input.generics);
+            let generic_bounds = to_generic_bounds(&input.generics);
+
+            let into_error_body =
+                to_constructor(input, &fields_info, "__source", context_from_self);
+
+            let mut expand_into_error_for =
+                |target_for: &dyn ToTokens, variant_prefix: &dyn ToTokens| {
+                    global.output.extend(quote!(
+                        #[allow(non_camel_case_types)]
+                        impl<#impl_params #magic_params #impl_kst_params>
+                        #RT::IntoErrorFor<#target_for>
+                        for #name<#ty_params #magic_params #ty_kst_params>
+                        where #generic_bounds #magic_bounds {
+                            fn into_error_for(self, __source: #source) -> #target_for {
+                                #RT::Into::<#target_for>::into(
+                                    #variant_prefix #orig_name #into_error_body
+                                )
+                            }
+                        }
+                    ));
+                };
+            let variant_prefix = to_variant_prefix(input, mirror);
+            remote
+                .iter()
+                .skip(1)
+                .for_each(|ty| expand_into_error_for(ty, &variant_prefix));
+            for group in attrs.group.iter() {
+                let group_name = to_group_name(input, group);
+                expand_into_error_for(&group_name, &quote!(#group_name::));
+            }
+        }
 
-            let variant_prefix = to_variant_prefix(input);
-            let into_error_body = to_constructor(self.input, &fields_info, "__source");
+        /* ---------------------- *
+         * generate builder impls *
+         * ---------------------- */
+
+        let builder = attrs
+            .builder
+            // inherit #[thisctx(builder)]
+            .or_else(|| parent_attrs.and_then(|a| a.builder))
+            .unwrap_or(false);
+        if builder {
+            let (impl_params, impl_kst_params) = split_generic_params(&input.generics, true, false);
+            let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+            let generic_bounds = to_generic_bounds(&input.generics);
+            let where_clause = input.generics.where_clause.as_ref();
 
+            let builder_name = format_ident!("{}Builder", name, span = name.span());
+            let marker = to_phantom_marker(&input.generics);
+            let fields = fields_info.context_fields().collect::<Vec<_>>();
+            // Each field is tracked by a type parameter, which is `Unset` until
+            // the corresponding setter is called:
+            //
+            // MyErrorBuilder<S_field1, S_field2> {
+            //     field1: S_field1,
+            //     field2: S_field2,
+            // }
+            let slots = fields
+                .iter()
+                .map(|(i, f)| format_ident!("S_{}", f.to_arg_ident(*i)))
+                .collect::<Vec<_>>();
+
+            // definition
+            let all_slots = to_slots(&slots, None);
+            let def_fields = to_builder_fields(&fields, |k, _| slots[k].to_token_stream());
             global.output.extend(quote!(
                 #[allow(non_camel_case_types)]
-                impl<#impl_params #magic_params #impl_kst_params> #RT::IntoError
-                for #name<#ty_params #magic_params #ty_kst_params>
-                where #geneirc_bounds #magic_bounds {
+                #vis struct #builder_name<#impl_params #all_slots #impl_kst_params>
+                #where_clause {
+                    #def_fields
+                    __marker: #RT::PhantomData<fn() -> (#marker)>,
+                }
+            ));
+
+            // Ctx::builder()
+            let default_tys = QuoteWith(|tokens| {
+                for (_, f) in fields.iter() {
+                    if f.generic.is_some() {
+                        f.ty.to_tokens(tokens);
+                        NewToken![,].to_tokens(tokens);
+                    }
+                }
+            });
+            let unset_slots = QuoteWith(|tokens| {
+                for _ in slots.iter() {
+                    RT_UNSET.to_tokens(tokens);
+                    NewToken![,].to_tokens(tokens);
+                }
+            });
+            let unset_fields = to_builder_fields(&fields, |_, _| RT_UNSET.to_token_stream());
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #impl_kst_params>
+                #name<#ty_params #default_tys #ty_kst_params>
+                where #generic_bounds {
+                    #vis fn builder() -> #builder_name<#ty_params #unset_slots #ty_kst_params> {
+                        #builder_name {
+                            #unset_fields
+                            __marker: #RT::PhantomData,
+                        }
+                    }
+                }
+            ));
+
+            // setters
+            for (k, (i, f)) in fields.iter().enumerate() {
+                let setter = f.to_arg_ident(*i);
+                let (setter_generic, setter_bound) = match &f.generic {
+                    Some(g) => (Some(g), Some(f.to_generic_bound())),
+                    None => (None, None),
+                };
+                let value_ty = f.to_def_ty();
+
+                let other_slots = QuoteWith(|tokens| {
+                    for (j, s) in slots.iter().enumerate() {
+                        if j != k {
+                            s.to_tokens(tokens);
+                            NewToken![,].to_tokens(tokens);
+                        }
+                    }
+                });
+                let self_slots = to_slots(&slots, Some((k, &RT_UNSET)));
+                let ret_slots = to_slots(&slots, Some((k, &value_ty)));
+                let setter_body = to_builder_fields(&fields, |j, arg| {
+                    if j == k {
+                        arg.to_token_stream()
+                    } else {
+                        quote!(self.#arg)
+                    }
+                });
+                global.output.extend(quote!(
+                    #[allow(non_camel_case_types)]
+                    impl<#impl_params #other_slots #impl_kst_params>
+                    #builder_name<#ty_params #self_slots #ty_kst_params>
+                    where #generic_bounds {
+                        #vis fn #setter<#setter_generic>(self, #setter: #value_ty)
+                            -> #builder_name<#ty_params #ret_slots #ty_kst_params>
+                        where #setter_bound {
+                            #builder_name {
+                                #setter_body
+                                __marker: #RT::PhantomData,
+                            }
+                        }
+                    }
+                ));
+            }
+
+            // Builder::build_context() and IntoError impl
+            let ctx_slots = QuoteWith(|tokens| {
+                for (_, f) in fields.iter() {
+                    f.to_def_ty().to_tokens(tokens);
+                    NewToken![,].to_tokens(tokens);
+                }
+            });
+            let ctx_fields = QuoteWith(|tokens| {
+                for (i, f) in fields.iter() {
+                    let member = f.to_context_member(*i);
+                    let arg = f.to_arg_ident(*i);
+                    tokens.extend(quote!(#member: self.#arg,));
+                }
+            });
+            global.output.extend(quote!(
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params>
+                #builder_name<#ty_params #ctx_slots #ty_kst_params>
+                where #generic_bounds {
+                    #vis fn build_context(self) -> #name<#ty_params #magic_params #ty_kst_params> {
+                        #name { #ctx_fields }
+                    }
+                }
+
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #impl_kst_params> #RT::ErrorContext
+                for #builder_name<#ty_params #ctx_slots #ty_kst_params>
+                where #generic_bounds #magic_bounds {
                     type Target = #target;
                     type Source = #source;
-                    fn into_error(self, __source: #source) -> #target {
-                        #RT::Into::<#target>::into(
-                            #variant_prefix #orig_name #into_error_body
-                        )
+                }
+
+                #[allow(non_camel_case_types)]
+                impl<#impl_params #magic_params #source_param #impl_kst_params>
+                #RT::IntoError #source_arg
+                for #builder_name<#ty_params #ctx_slots #ty_kst_params>
+                where #generic_bounds #magic_bounds #source_bound {
+                    fn into_error(self, __source: #source_arg_ty) -> #target {
+                        #RT::IntoError::into_error(self.build_context(), __source)
                     }
                 }
             ));
@@ -292,23 +1770,188 @@ impl<'i> ContextInfo<'i, '_> {
          * -------------------------- */
 
         if let Some(i) = fields_info.from_field {
-            let from_ty = &fields_info[i].ty;
-            let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+            let field_ty = fields_info[i].to_source_ty();
+            let from_args = fields_info[i].attrs.from.as_ref().unwrap();
+
+            let variant_prefix = to_variant_prefix(input, mirror);
+            let from_body = to_constructor(input, &fields_info, "__source", context_from_default);
+            let from_body = quote!({
+                let __source = #RT::Into::<#field_ty>::into(__value);
+                #RT::Into::<#target>::into(
+                    #variant_prefix #orig_name #from_body
+                )
+            });
 
-            let variant_prefix = to_variant_prefix(input);
-            let from_body = to_constructor(self.input, &fields_info, "__value");
+            // conversions from the same type are collected and expanded
+            // together, see `generate From<T> impls`
+            let mut add_from = |from_ty: &dyn ToTokens| {
+                let from_impl = global
+                    .from_impls
+                    .entry(quote!(#from_ty for #target).to_string())
+                    .or_insert_with(|| FromImpl {
+                        from_ty: from_ty.to_token_stream(),
+                        target: target.to_token_stream(),
+                        branches: Vec::new(),
+                    });
+                from_impl.branches.push(FromBranch {
+                    span: orig_name.span(),
+                    when: from_args.when.as_ref().map(ToTokens::to_token_stream),
+                    body: from_body.clone(),
+                });
+            };
+            if from_args.types.is_empty() {
+                // thiserror has already implemented From<#field_ty> for #[from]
+                if !fields_info[i].attrs.thiserror_from {
+                    add_from(field_ty);
+                }
+            } else {
+                from_args.types.iter().for_each(|ty| add_from(ty));
+            }
+        }
 
-            global.output.extend(quote!(
-                #[allow(non_camel_case_types)]
-                impl #impl_generics #RT::From<#from_ty> for #target
-                #where_clause {
-                    fn from(__value: #from_ty) -> #target {
-                        #RT::Into::<#target>::into(
-                            #variant_prefix #orig_name #from_body
-                        )
+        /* ------------------------ *
+         * generate TryFrom<#input> *
+         * ------------------------ */
+
+        // impl TryFrom<#input> for (#context, #source)
+        //
+        // Shared sources and flattened fields cannot be recovered from the
+        // error, and the target of remote or mirror is not the input.
+        let decomposable = remote.is_empty()
+            && mirror.is_none()
+            && fields_info.iter().all(|f| {
+                f.attrs.flatten.is_none() && f.attrs.source_mode != Some(SourceMode::Shared)
+            });
+        if decomposable {
+            let input_name = &input.ident;
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            let (ty_params, ty_kst_params) = split_generic_params(&input.generics, false, false);
+            let variant_prefix = to_variant_prefix(input, None);
+
+            // Input::Variant { field: __0, 1: __1, 2: _ }
+            //                                      ^ excluded fields are dropped
+            let pattern = QuoteWith(|tokens| {
+                for (i, f) in fields_info.iter().enumerate() {
+                    to_member(f, i).to_tokens(tokens);
+                    NewToken![:].to_tokens(tokens);
+                    if f.attrs.source || !f.attrs.is_excluded() {
+                        format_ident!("__{}", i).to_tokens(tokens);
+                    } else {
+                        NewToken![_].to_tokens(tokens);
                     }
+                    NewToken![,].to_tokens(tokens);
                 }
-            ));
+            });
+            let context_tys = QuoteWith(|tokens| {
+                for (_, f) in fields_info.context_fields() {
+                    if f.generic.is_some() {
+                        f.ty.to_tokens(tokens);
+                        NewToken![,].to_tokens(tokens);
+                    }
+                }
+            });
+            let context_fields = QuoteWith(|tokens| {
+                let mut index = 0usize;
+                for (i, f) in fields_info.iter().enumerate() {
+                    if f.attrs.is_excluded() {
+                        continue;
+                    }
+                    let member = f.to_context_member(index);
+                    let binding = format_ident!("__{}", i);
+                    tokens.extend(quote!(#member: #binding,));
+                    index += 1;
+                }
+            });
+            let source_value = QuoteWith(|tokens| {
+                if let Some(i) = fields_info.source_field {
+                    format_ident!("__{}", i).to_tokens(tokens);
+                } else {
+                    tokens.extend(quote!(#RT::NoneSource));
+                }
+            });
+            let parts_ty = quote!((#name<#ty_params #context_tys #ty_kst_params>, #source));
+            let parts = quote!((#name { #context_fields }, #source_value));
+
+            global
+                .output
+                .extend(if matches!(input.data, syn::Data::Enum(_)) {
+                    quote!(
+                        #[allow(non_camel_case_types)]
+                        impl #impl_generics #RT::TryFrom<#input_name #ty_generics> for #parts_ty
+                        #where_clause {
+                            type Error = #input_name #ty_generics;
+                            #[allow(unreachable_patterns)]
+                            fn try_from(
+                                __value: #input_name #ty_generics,
+                            ) -> #RT::Result<Self, #input_name #ty_generics> {
+                                match __value {
+                                    #variant_prefix #orig_name { #pattern } => #RT::Ok(#parts),
+                                    __value => #RT::Err(__value),
+                                }
+                            }
+                        }
+                    )
+                } else {
+                    quote!(
+                        #[allow(non_camel_case_types)]
+                        impl #impl_generics #RT::From<#input_name #ty_generics> for #parts_ty
+                        #where_clause {
+                            fn from(__value: #input_name #ty_generics) -> Self {
+                                let #input_name { #pattern } = __value;
+                                #parts
+                            }
+                        }
+                    )
+                });
+        }
+
+        /* ------------------------------ *
+         * generate inherent constructors *
+         * ------------------------------ */
+
+        let constructors = attrs
+            .constructors
+            // inherit #[thisctx(constructors)]
+            .or_else(|| parent_attrs.and_then(|a| a.constructors))
+            .unwrap_or(false);
+        if constructors && mirror.is_some() {
+            return Err(self.error("`constructors` conflicts with `mirror`"));
+        }
+        if constructors {
+            // Error::my_variant(source, field_1, field_2)
+            let fn_name = crate::util::to_snake_case(orig_name);
+            if fields_info.source_field.is_some() {
+                if let Some((_, f)) = fields_info
+                    .context_fields()
+                    .find(|(_, f)| f.context_ident().map_or(false, |i| i == "source"))
+                {
+                    return Err(syn::Error::new(
+                        f.context_ident().unwrap().span(),
+                        "the field `source` conflicts with the source argument of the \
+                         constructor, consider renaming it with `#[thisctx(rename = <name>)]`",
+                    ));
+                }
+            }
+            let source_arg = QuoteWith(|tokens| {
+                if fields_info.source_field.is_some() {
+                    tokens.extend(quote!(source: #source,));
+                }
+            });
+            let args = fields_info.to_args();
+
+            let variant_prefix = to_variant_prefix(input, mirror);
+            let body = to_constructor(input, &fields_info, "source", context_from_args);
+
+            global.methods.push(
+                &fn_name,
+                quote!(
+                    #[allow(non_camel_case_types, clippy::too_many_arguments)]
+                    #input_vis fn #fn_name<#magic_params>(#source_arg #args) -> Self
+                    where #magic_bounds {
+                        #variant_prefix #orig_name #body
+                    }
+                ),
+            )?;
         }
         Ok(())
     }
@@ -327,9 +1970,16 @@ impl<'i> ContextInfo<'i, '_> {
         let mut field_named_source = None;
         let mut len = 0;
         let mut optionals_count = 0;
+        let mut defaults_count = 0;
+        let mut backtraces_count = 0;
         for (i, field) in self.fields.iter().enumerate() {
             len += 1;
             let f_attrs = crate::attrs::parse_field(field)?;
+            if parent_attrs.map_or(false, |a| a.mirror.is_some())
+                && (f_attrs.from.is_some() || f_attrs.optional.is_some())
+            {
+                return Err(self.error("`from` and `optional` are not supported with `mirror`"));
+            }
 
             // check source field
             if f_attrs.source {
@@ -338,18 +1988,26 @@ impl<'i> ContextInfo<'i, '_> {
                 }
                 source_field = Some(i);
             }
-            if field.ident.as_ref().map_or(false, |i| i == "source") {
+            // a flattened field is never inferred as the source
+            if f_attrs.flatten.is_none() && field.ident.as_ref().map_or(false, |i| i == "source") {
                 field_named_source = Some(i);
             }
 
             // check from field
-            if f_attrs.from {
+            if f_attrs.from.is_some() {
                 if from_field.is_some() {
                     return Err(self.error("duplicate from fields"));
                 }
                 from_field = Some(i);
             }
 
+            if f_attrs.default.is_some() {
+                defaults_count += 1;
+            }
+            if f_attrs.backtrace && !f_attrs.source {
+                backtraces_count += 1;
+            }
+
             // collect optional fields
             if let Some(optional) = &f_attrs.optional {
                 optionals_count += 1;
@@ -381,12 +2039,42 @@ impl<'i> ContextInfo<'i, '_> {
             }
 
             field_infos.push(FieldInfo {
-                i: field,
+                i: Cow::Borrowed(field),
                 attrs: f_attrs,
                 parent_vis: self.vis,
                 generic: None,
+                source_ty: None,
+                unboxed_ty: None,
+                nested_in: None,
             });
         }
+
+        // Fields of flattened structs are appended to the end, so that the
+        // indices of the original fields are kept.
+        for i in 0..field_infos.len() {
+            let f = &field_infos[i];
+            let names = match &f.attrs.flatten {
+                Some(names) => names.clone(),
+                None => continue,
+            };
+            let vis = f.attrs.vis.clone();
+            for name in names {
+                let mut attrs = crate::attrs::parse_field(&syn::parse_quote!(#name: ()))?;
+                // inherit #[thisctx(vis)] of the flattened field
+                attrs.vis = vis.clone();
+                field_infos.push(FieldInfo {
+                    // The type is only used in the builder, where a flattened
+                    // field is always generic.
+                    i: Cow::Owned(syn::parse_quote!(#name: #RT::Unset)),
+                    attrs,
+                    parent_vis: self.vis,
+                    generic: None,
+                    source_ty: None,
+                    unboxed_ty: None,
+                    nested_in: Some(i),
+                });
+            }
+        }
         if attrs.transparent {
             if len != 1 {
                 return Err(self.error("a transparent context must have exactly 1 field"));
@@ -396,10 +2084,11 @@ impl<'i> ContextInfo<'i, '_> {
             source_field = Some(0);
             field_infos[0].attrs.source = true;
         } else if let Some(i) = from_field {
-            if (len - optionals_count) != 1 {
-                return Err(
-                    self.error("`from` requires exactly 1 field (excluding optional fields)")
-                );
+            if (len - optionals_count - defaults_count - backtraces_count) != 1 {
+                return Err(self.error(
+                    "`from` requires exactly 1 field (excluding optional, default and backtrace \
+                     fields)",
+                ));
             }
             // From attributes always implies that the same field is source.
             source_field = from_field;
@@ -410,7 +2099,38 @@ impl<'i> ContextInfo<'i, '_> {
             field_infos[i].attrs.source = true;
         }
 
+        // resolve the wrapped source
+        if let Some(i) = source_field {
+            let f = &mut field_infos[i];
+            if f.attrs.source_mode == Some(SourceMode::Shared) {
+                match crate::infer::get_shared_inner(&f.ty) {
+                    Some(inner) => f.source_ty = Some(inner.clone()),
+                    None => {
+                        return Err(syn::Error::new_spanned(
+                            &f.ty,
+                            "`source(shared)` requires an `Arc<_>` or `Rc<_>` field",
+                        ))
+                    }
+                }
+            } else if f.attrs.source_mode.is_none()
+                && crate::infer::is_boxed_self(&f.ty, &self.input.ident)
+            {
+                // `Box<Self>` also accepts the unboxed error as the source
+                let input_name = &self.input.ident;
+                let (_, ty_generics, _) = self.input.generics.split_for_impl();
+                let unboxed: syn::Type = syn::parse_quote!(#input_name #ty_generics);
+                // `Self` would refer to the context in generated impls
+                f.source_ty = Some(crate::infer::replace_boxed(&f.ty, unboxed.clone()));
+                f.unboxed_ty = Some(unboxed);
+            }
+        }
+
         // 2nd-pass: add generics
+        let sealed = attrs
+            .sealed
+            // inherit #[thisctx(sealed)]
+            .or_else(|| parent_attrs.and_then(|a| a.sealed))
+            .unwrap_or(false);
         let parent_magic = attrs
             .magic
             // inherit #[thisctx(magic)]
@@ -420,12 +2140,15 @@ impl<'i> ContextInfo<'i, '_> {
                 continue;
             }
 
-            if f.attrs
-                .magic
-                .or(parent_magic)
-                .unwrap_or_else(|| crate::infer::is_in_magic_whitelist(&f.ty))
+            // Fields of flattened structs are always generic, since they are
+            // converted by the nested struct.
+            if f.nested_in.is_some()
+                || f.attrs
+                    .magic
+                    .or(parent_magic)
+                    .unwrap_or_else(|| crate::infer::is_in_magic_whitelist(&f.ty))
             {
-                f.generic = Some(match &f.ident {
+                f.generic = Some(match f.context_ident() {
                     Some(i) => format_ident!("T_{}", i, span = i.span()),
                     None => format_ident!("T_{}", i),
                 });
@@ -438,6 +2161,7 @@ impl<'i> ContextInfo<'i, '_> {
             i: field_infos,
             source_field,
             from_field,
+            sealed,
         })
     }
 
@@ -446,10 +2170,58 @@ impl<'i> ContextInfo<'i, '_> {
     }
 }
 
+/// Returns the fields of a constructor that moves each field of `input` into
+/// the member with the same name.
+pub(crate) fn to_into_error_fields(
+    input: &DeriveInput,
+    fields: &Fields,
+    source_member: Option<&Ident>,
+) -> syn::Result<TokenStream> {
+    let attrs = crate::attrs::parse_container(input)?;
+    let context = ContextInfo {
+        input,
+        name: &input.ident,
+        fields,
+        parent_attrs: None,
+        attrs: &attrs,
+        vis: &input.vis,
+    };
+    let mut fields_info = context.parse_fields_info(&mut GlobalData::default())?;
+    // The source is specified by `#[into_error(source = ..)]`, so all fields
+    // are copied to the target.
+    if let Some(i) = fields_info.source_field.take() {
+        fields_info.i[i].attrs.source = false;
+    }
+    if let Some(member) = source_member {
+        if let Some(f) = fields_info
+            .iter()
+            .find(|f| f.ident.as_ref() == Some(member))
+        {
+            return Err(syn::Error::new_spanned(
+                &*f.i,
+                format!(
+                    "the field `{}` conflicts with the source of `target`, consider renaming it",
+                    member
+                ),
+            ));
+        }
+    }
+    for f in fields_info.i.iter_mut() {
+        // infer the field types of the target
+        f.i.to_mut().ty = syn::parse_quote!(_);
+    }
+    Ok(
+        to_constructor_fields(input, &fields_info, "__source", context_from_self)
+            .into_token_stream(),
+    )
+}
+
 fn to_constructor<'a>(
-    _input: &'a DeriveInput,
+    input: &'a DeriveInput,
     fields: &'a FieldsInfo,
     source: &'static str,
+    // gets the value of a context field by its index
+    context: impl 'a + Fn(&FieldInfo, usize) -> TokenStream,
 ) -> impl 'a + ToTokens {
     // Tuple structs can be constructed using indices:
     //
@@ -458,28 +2230,93 @@ fn to_constructor<'a>(
     //     1: "field_1",
     //     ..
     // };
-    let fields = QuoteWith(move |tokens| {
+    let fields = to_constructor_fields(input, fields, source, context);
+    Group::new(Delimiter::Brace, fields.into_token_stream())
+}
+
+fn to_constructor_fields<'a>(
+    _input: &'a DeriveInput,
+    fields: &'a FieldsInfo,
+    source: &'static str,
+    context: impl 'a + Fn(&FieldInfo, usize) -> TokenStream,
+) -> impl 'a + ToTokens {
+    QuoteWith(move |tokens| {
         let mut shift = 0usize;
         for (i, f) in fields.iter().enumerate() {
+            if f.nested_in.is_some() {
+                // assigned to the flattened struct
+                continue;
+            }
             to_member(f, i).to_tokens(tokens);
             NewToken![:].to_tokens(tokens);
             let ty = &f.ty;
             tokens.extend(if f.attrs.source {
                 shift += 1;
-                Ident::new(source, Span::call_site()).into_token_stream()
+                let source = Ident::new(source, Span::call_site());
+                if f.attrs.source_mode == Some(SourceMode::Shared) {
+                    // wrap the source in its shared pointer
+                    quote!(#RT::From::from(#source))
+                } else {
+                    source.into_token_stream()
+                }
             } else if f.attrs.optional.is_some() {
                 shift += 1;
                 quote!(<#ty as #RT::Default>::default())
+            } else if f.attrs.backtrace {
+                shift += 1;
+                // works on both `Backtrace` and `Option<Backtrace>`
+                quote!(#RT::From::from(::std::backtrace::Backtrace::capture()))
+            } else if f.attrs.flatten.is_some() {
+                shift += 1;
+                // check each name against the index of the nested field
+                let checks = QuoteWith(|tokens| {
+                    let names = f.attrs.flatten.iter().flatten();
+                    for (i, name) in names.enumerate() {
+                        let index_const = format_ident!("__flatten_{}", name, span = name.span());
+                        tokens.extend(quote_spanned!(name.span()=>
+                            let _: #RT::FlattenIndex<#i> = <#ty>::#index_const;
+                        ));
+                    }
+                });
+                // build the nested struct from the values of its fields
+                let values = QuoteWith(|tokens| {
+                    for nested in fields.iter().filter(|n| n.nested_in == Some(i)) {
+                        // flattened fields are always named
+                        context(nested, 0).to_tokens(tokens);
+                        NewToken![,].to_tokens(tokens);
+                    }
+                });
+                quote!({
+                    #checks
+                    #RT::Flatten::flatten((#values))
+                })
             } else {
                 // shift excluded fields to get the correct member index
-                let member = to_member(f, i - shift);
+                let value = context(f, i - shift);
                 // Into::into works on both generic and non-generic fields
-                quote!(#RT::Into::<#ty>::into(self.#member))
+                quote!(#RT::Into::<#ty>::into(#value))
             });
             NewToken![,].to_tokens(tokens);
         }
-    });
-    Group::new(Delimiter::Brace, fields.into_token_stream())
+    })
+}
+
+fn context_from_self(f: &FieldInfo, index: usize) -> TokenStream {
+    let member = f.to_context_member(index);
+    quote!(self.#member)
+}
+
+fn context_from_default(f: &FieldInfo, _: usize) -> TokenStream {
+    let ty = &f.ty;
+    match &f.attrs.default {
+        // ensure the expression is inferred as the field type
+        Some(Some(expr)) => quote!(#RT::identity::<#ty>(#expr)),
+        _ => quote!(<#ty as #RT::Default>::default()),
+    }
+}
+
+fn context_from_args(f: &FieldInfo, index: usize) -> TokenStream {
+    f.to_arg_ident(index).into_token_stream()
 }
 
 fn to_with_optional_body<'a>(
@@ -493,7 +2330,7 @@ fn to_with_optional_body<'a>(
     //     1: field_1,
     //     ..
     // } = my_tuple;
-    let variant_prefix = to_variant_prefix(input);
+    let variant_prefix = to_variant_prefix(input, None);
     let match_arms = QuoteWith(move |tokens| {
         for OptionalField {
             parent,
@@ -521,6 +2358,7 @@ struct FieldsInfo<'a> {
     i: Vec<FieldInfo<'a>>,
     source_field: Option<usize>,
     from_field: Option<usize>,
+    sealed: bool,
 }
 
 impl<'a> ops::Deref for FieldsInfo<'a> {
@@ -532,27 +2370,55 @@ impl<'a> ops::Deref for FieldsInfo<'a> {
 }
 
 impl FieldsInfo<'_> {
+    /// Iterates over fields of the generated context with their indices.
+    fn context_fields(&self) -> impl '_ + Iterator<Item = (usize, &FieldInfo)> {
+        self.iter().filter(|f| !f.attrs.is_excluded()).enumerate()
+    }
+
     fn to_def(&self) -> impl '_ + ToTokens {
         QuoteWith(move |tokens| {
-            for f in self.iter() {
-                if f.attrs.is_excluded() {
-                    continue;
-                }
+            for (_, f) in self.context_fields() {
                 f.attrs.to_outer_attrs().to_tokens(tokens);
-                f.parent_vis.to_tokens(tokens);
-                f.ident.to_tokens(tokens);
-                f.colon_token.to_tokens(tokens);
-                if let Some(g) = &f.generic {
-                    // replace the original type with the generic identifier
-                    g.to_tokens(tokens);
-                } else {
-                    f.ty.to_tokens(tokens);
+                if let Some(vis) = &f.attrs.vis {
+                    vis.to_tokens(tokens);
+                } else if !self.sealed {
+                    // inherit #[thisctx(vis)]
+                    f.parent_vis.to_tokens(tokens);
                 }
+                f.context_ident().to_tokens(tokens);
+                f.colon_token.to_tokens(tokens);
+                f.to_def_ty().to_tokens(tokens);
                 NewToken![,].to_tokens(tokens);
             }
         })
     }
 
+    fn to_args(&self) -> impl '_ + ToTokens {
+        QuoteWith(move |tokens| {
+            for (i, f) in self.context_fields() {
+                let arg = f.to_arg_ident(i);
+                let ty = f.to_def_ty();
+                tokens.extend(quote!(#arg: #ty,));
+            }
+        })
+    }
+
+    fn to_self_constructor(&self) -> impl '_ + ToTokens {
+        // Self {
+        //     field_0: field_0,
+        //     1: _1,
+        //     ..
+        // }
+        let fields = QuoteWith(move |tokens| {
+            for (i, f) in self.context_fields() {
+                let member = f.to_context_member(i);
+                let arg = f.to_arg_ident(i);
+                tokens.extend(quote!(#member: #arg,));
+            }
+        });
+        quote!(Self { #fields })
+    }
+
     fn to_generic_params(&self, with_defaults: bool) -> impl '_ + ToTokens {
         QuoteWith(move |tokens| {
             for f in self.iter() {
@@ -579,10 +2445,19 @@ impl FieldsInfo<'_> {
 
     fn to_generic_bounds(&self) -> impl '_ + ToTokens {
         QuoteWith(move |tokens| {
-            for f in self.iter() {
-                if let Some(g) = &f.generic {
+            for (i, f) in self.iter().enumerate() {
+                if f.attrs.flatten.is_some() {
+                    // Field: Flatten<(T_nested1, T_nested2,)>
                     let ty = &f.ty;
-                    tokens.extend(quote!(#g: #RT::Into::<#ty>,));
+                    let nested = QuoteWith(|tokens| {
+                        for n in self.iter().filter(|n| n.nested_in == Some(i)) {
+                            n.generic.to_tokens(tokens);
+                            NewToken![,].to_tokens(tokens);
+                        }
+                    });
+                    tokens.extend(quote!(#ty: #RT::Flatten<(#nested)>,));
+                } else if f.generic.is_some() {
+                    f.to_generic_bound().to_tokens(tokens);
                 }
             }
         })
@@ -590,17 +2465,77 @@ impl FieldsInfo<'_> {
 }
 
 struct FieldInfo<'a> {
-    i: &'a Field,
+    i: Cow<'a, Field>,
     attrs: Attrs,
     parent_vis: &'a Visibility,
     generic: Option<Ident>,
+    // the source type, if it is wrapped before being stored
+    source_ty: Option<syn::Type>,
+    // the unboxed error, which is also accepted by a `Box<Self>` source
+    unboxed_ty: Option<syn::Type>,
+    // the index of the flattened field which this field belongs to
+    nested_in: Option<usize>,
+}
+
+impl FieldInfo<'_> {
+    /// Returns the identifier of this field in the generated context.
+    fn context_ident(&self) -> Option<&Ident> {
+        self.attrs.rename.as_ref().or(self.ident.as_ref())
+    }
+
+    /// Returns the type of this field in the generated context.
+    fn to_def_ty(&self) -> impl '_ + ToTokens {
+        QuoteWith(move |tokens| {
+            if let Some(g) = &self.generic {
+                // replace the original type with the generic identifier
+                g.to_tokens(tokens);
+            } else {
+                self.ty.to_tokens(tokens);
+            }
+        })
+    }
+
+    /// Returns the type accepted by this field as the source.
+    fn to_source_ty(&self) -> &syn::Type {
+        self.source_ty.as_ref().unwrap_or(&self.ty)
+    }
+
+    fn to_generic_bound(&self) -> impl '_ + ToTokens {
+        QuoteWith(move |tokens| {
+            if let Some(g) = &self.generic {
+                // fields of flattened structs are bound by the nested struct
+                if self.nested_in.is_none() {
+                    let ty = &self.ty;
+                    tokens.extend(quote!(#g: #RT::Into::<#ty>,));
+                }
+            }
+        })
+    }
+
+    /// Returns the argument name of this field in generated functions.
+    fn to_arg_ident(&self, index: usize) -> Ident {
+        match self.context_ident() {
+            Some(i) => i.clone(),
+            None => format_ident!("_{}", index),
+        }
+    }
+
+    fn to_context_member(&self, index: usize) -> impl '_ + ToTokens {
+        QuoteWith(move |tokens| {
+            if let Some(i) = self.context_ident() {
+                i.to_tokens(tokens);
+            } else {
+                to_member(self, index).to_tokens(tokens);
+            }
+        })
+    }
 }
 
 impl ops::Deref for FieldInfo<'_> {
     type Target = Field;
 
     fn deref(&self) -> &Self::Target {
-        self.i
+        &self.i
     }
 }
 
@@ -621,9 +2556,9 @@ impl Attrs {
     }
 
     fn is_excluded(&self) -> bool {
-        // Source field and optional fields are excluded from the generated
-        // context fields.
-        self.source || self.optional.is_some()
+        // Source field, optional fields, backtrace fields and flattened fields
+        // are excluded from the generated context fields.
+        self.source || self.optional.is_some() || self.backtrace || self.flatten.is_some()
     }
 
     fn to_outer_attrs(&self) -> Option<impl '_ + ToTokens> {
@@ -710,9 +2645,87 @@ fn to_generic_bounds(generics: &Generics) -> impl '_ + ToTokens {
     })
 }
 
-fn to_variant_prefix(input: &DeriveInput) -> impl '_ + ToTokens {
+fn to_slots<'a>(
+    slots: &'a [Ident],
+    replace: Option<(usize, &'a dyn ToTokens)>,
+) -> impl 'a + ToTokens {
+    // replace the k-th slot with the given type if specified
+    QuoteWith(move |tokens| {
+        for (i, s) in slots.iter().enumerate() {
+            match replace {
+                Some((k, slot)) if k == i => slot.to_tokens(tokens),
+                _ => s.to_tokens(tokens),
+            }
+            NewToken![,].to_tokens(tokens);
+        }
+    })
+}
+
+fn to_builder_fields<'a>(
+    fields: &'a [(usize, &FieldInfo)],
+    value: impl 'a + Fn(usize, &Ident) -> TokenStream,
+) -> impl 'a + ToTokens {
+    QuoteWith(move |tokens| {
+        for (k, (i, f)) in fields.iter().enumerate() {
+            let arg = f.to_arg_ident(*i);
+            let value = value(k, &arg);
+            tokens.extend(quote!(#arg: #value,));
+        }
+    })
+}
+
+fn to_phantom_marker(generics: &Generics) -> impl '_ + ToTokens {
+    // Captures all non-const parameters to avoid unused parameters:
+    //
+    // PhantomData<fn() -> (&'a (), *const T, ..)>
+    QuoteWith(move |tokens| {
+        for param in generics.params.iter() {
+            match param {
+                GenericParam::Lifetime(l) => {
+                    let l = &l.lifetime;
+                    tokens.extend(quote!(&#l (),));
+                }
+                GenericParam::Type(t) => {
+                    let t = &t.ident;
+                    tokens.extend(quote!(*const #t,));
+                }
+                _ => continue,
+            }
+        }
+    })
+}
+
+/// Checks whether an attribute is `derive(.., Error, ..)`.
+fn is_derive_error(attr: &TokenStream) -> bool {
+    match syn::parse2::<syn::Meta>(attr.clone()) {
+        Ok(syn::Meta::List(list)) if list.path.is_ident("derive") => list
+            .parse_args_with(
+                syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+            )
+            .map_or(false, |paths| {
+                paths
+                    .iter()
+                    .any(|p| p.segments.last().map_or(false, |s| s.ident == "Error"))
+            }),
+        _ => false,
+    }
+}
+
+fn to_group_name(input: &DeriveInput, group: &Ident) -> Ident {
+    // #[thisctx(group = Parse)] on `Error` => `ParseError`
+    format_ident!("{}{}", group, input.ident, span = group.span())
+}
+
+fn to_variant_prefix<'a>(
+    input: &'a DeriveInput,
+    mirror: Option<&'a syn::Path>,
+) -> impl 'a + ToTokens {
     QuoteWith(move |tokens| {
-        if matches!(input.data, syn::Data::Enum(_)) {
+        if let Some(mirror) = mirror {
+            // construct variants of the mirrored enum directly
+            mirror.to_tokens(tokens);
+            NewToken![::].to_tokens(tokens);
+        } else if matches!(input.data, syn::Data::Enum(_)) {
             // variant constructor requires enum prefix
             input.ident.to_tokens(tokens);
             NewToken![::].to_tokens(tokens);
@@ -24,6 +24,43 @@ pub(crate) fn get_optional_inner(ty: &Type) -> Option<&Type> {
     }
 }
 
+/// Returns the pointee type of a shared pointer.
+pub(crate) fn get_shared_inner(ty: &Type) -> Option<&Type> {
+    let (name, _) = infer_std(ty)?;
+    if matches_any!(name, "Arc", "Rc") {
+        get_optional_inner(ty)
+    } else {
+        None
+    }
+}
+
+/// Checks whether a type is `Box<Self>` or `Box<#name>`.
+pub(crate) fn is_boxed_self(ty: &Type, name: &Ident) -> bool {
+    let is_box = infer_std(ty).map_or(false, |(n, _)| n == "Box");
+    match get_optional_inner(ty) {
+        Some(Type::Path(p)) if is_box && p.qself.is_none() => {
+            p.path.is_ident("Self")
+                || p.path.segments.len() == 1 && p.path.segments[0].ident == *name
+        }
+        _ => false,
+    }
+}
+
+/// Replaces the pointee of `Box<T>` with the given type.
+pub(crate) fn replace_boxed(ty: &Type, pointee: Type) -> Type {
+    let mut ty = ty.clone();
+    if let Type::Path(p) = &mut ty {
+        if let PathArguments::AngleBracketed(args) =
+            &mut p.path.segments.last_mut().unwrap().arguments
+        {
+            if let Some(GenericArgument::Type(inner)) = args.args.first_mut() {
+                *inner = pointee;
+            }
+        }
+    }
+    ty
+}
+
 pub(crate) fn is_in_magic_whitelist(ty: &Type) -> bool {
     if let Some((name, _)) = infer_std(ty) {
         matches_any!(name, "String", "PathBuf", "Vec", "Box", "Arc", "OsString", "CString", "Rc")
@@ -160,6 +197,42 @@ mod tests {
         test_not_in_magic_whitelist("some::magical::path::Arc");
     }
 
+    #[test]
+    fn infer_shared_inner() {
+        test_input_with("std::sync::Arc<std::io::Error>", |ty| {
+            assert!(super::get_shared_inner(ty).is_some());
+        });
+        test_input_with("Rc<String>", |ty| {
+            assert!(super::get_shared_inner(ty).is_some());
+        });
+        test_input_with("Box<String>", |ty| {
+            assert!(super::get_shared_inner(ty).is_none());
+        });
+    }
+
+    #[test]
+    fn infer_boxed_self() {
+        let name = syn::parse_str("Error").unwrap();
+        test_input_with("Box<Self>", |ty| assert!(super::is_boxed_self(ty, &name)));
+        test_input_with("Box<Error>", |ty| assert!(super::is_boxed_self(ty, &name)));
+        test_input_with("Box<Error<T>>", |ty| {
+            assert!(super::is_boxed_self(ty, &name))
+        });
+        test_input_with("Box<io::Error>", |ty| {
+            assert!(!super::is_boxed_self(ty, &name))
+        });
+        test_input_with("Arc<Self>", |ty| assert!(!super::is_boxed_self(ty, &name)));
+    }
+
+    #[test]
+    fn infer_replace_boxed() {
+        test_input_with("std::boxed::Box<Self>", |ty| {
+            let ty = super::replace_boxed(ty, syn::parse_str("Error").unwrap());
+            let inner = super::get_optional_inner(&ty).expect("failed to infer inner type");
+            assert!(matches!(inner, Type::Path(p) if p.path.is_ident("Error")));
+        });
+    }
+
     #[test]
     fn infer_optional_inner() {
         test_optional_inner_type("Option<String>", "String");
@@ -3,15 +3,24 @@
 #[macro_use]
 mod util;
 mod attrs;
+mod derive_into_error;
 mod derive_with_context;
 mod infer;
 
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(WithContext, attributes(error, source, thisctx))]
+#[proc_macro_derive(WithContext, attributes(backtrace, error, from, source, thisctx))]
 pub fn derive_with_context(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     derive_with_context::expand(input)
         .unwrap_or_else(syn::Error::into_compile_error)
         .into()
 }
+
+#[proc_macro_derive(IntoError, attributes(into_error))]
+pub fn derive_into_error(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    derive_into_error::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -1,4 +1,5 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Group, Ident, TokenStream, TokenTree};
+use quote::ToTokens;
 
 macro_rules! NewToken {
     ($($tt:tt)*) => {
@@ -24,3 +25,81 @@ where
         (self.0)(tokens)
     }
 }
+
+/// Replaces `Self` in the given tokens, e.g. `Box<Self>`, with another type.
+pub(crate) fn replace_self(tokens: TokenStream, ty: &TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|tt| match tt {
+            TokenTree::Ident(i) if i == "Self" => ty.clone(),
+            TokenTree::Group(g) => {
+                let mut replaced = Group::new(g.delimiter(), replace_self(g.stream(), ty));
+                replaced.set_span(g.span());
+                replaced.into_token_stream()
+            }
+            tt => tt.into_token_stream(),
+        })
+        .collect()
+}
+
+/// Converts a `CamelCase` identifier into `snake_case`, keywords are escaped as
+/// raw identifiers.
+pub(crate) fn to_snake_case(ident: &Ident) -> Ident {
+    let name = ident.to_string();
+    let chars = name.trim_start_matches("r#").chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(chars.len() + 4);
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            // split words at `aB` and `ABc`
+            let after_lower = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let before_lower = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).map_or(false, |c| c.is_lowercase());
+            if after_lower || before_lower {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    if syn::parse_str::<Ident>(&snake).is_ok() {
+        Ident::new(&snake, ident.span())
+    } else if matches!(snake.as_str(), "crate" | "self" | "super") {
+        // these keywords cannot be raw identifiers
+        Ident::new(&format!("{}_", snake), ident.span())
+    } else {
+        Ident::new_raw(&snake, ident.span())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::{Ident, Span};
+
+    fn test_snake_case(input: &str, expected: &str) {
+        let ident = Ident::new(input, Span::call_site());
+        let snake = super::to_snake_case(&ident);
+        if snake != expected {
+            panic!("{} != {}", snake, expected);
+        }
+    }
+
+    #[test]
+    fn snake_case() {
+        test_snake_case("NotFound", "not_found");
+        test_snake_case("Io", "io");
+        test_snake_case("IO", "io");
+        test_snake_case("HTTPError", "http_error");
+        test_snake_case("Utf8Error", "utf8_error");
+        test_snake_case("Already_Snake", "already_snake");
+        test_snake_case("snake_case", "snake_case");
+    }
+
+    #[test]
+    fn snake_case_keywords() {
+        test_snake_case("Type", "r#type");
+        test_snake_case("Loop", "r#loop");
+        test_snake_case("Super", "super_");
+    }
+}
@@ -0,0 +1,134 @@
+//! Integrations of [thisctx](https://crates.io/crates/thisctx) with web
+//! frameworks, the generated code refers to this crate if the corresponding
+//! attributes are specified.
+//!
+//! # 🧩 Optional features
+//!
+//! - `http`: `#[thisctx(status = <code>)]` generates `status()`, which returns
+//!   an `http::StatusCode`.
+//! - `axum`: `#[thisctx(response(axum))]` implements `IntoResponse` with a
+//!   problem details body of [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457).
+//! - `actix`: `#[thisctx(response(actix))]` implements `ResponseError` with the
+//!   same body.
+//! - `tonic`: `#[thisctx(grpc = <code>)]` converts errors into a
+//!   `tonic::Status`, add `#[thisctx(grpc_details)]` to attach the context
+//!   fields as metadata.
+#![no_std]
+
+#[cfg(feature = "serde_json")]
+extern crate alloc;
+
+/// **NOT PUBLIC APIS**
+#[doc(hidden)]
+pub mod private {
+    #[cfg(feature = "actix-web")]
+    pub use ::actix_web;
+    #[cfg(feature = "serde_json")]
+    pub use ::alloc::vec::Vec;
+    #[cfg(feature = "axum-core")]
+    pub use ::axum_core;
+    #[cfg(feature = "http")]
+    pub use ::http;
+    #[cfg(feature = "serde_json")]
+    pub use ::serde_json;
+    #[cfg(feature = "tonic")]
+    pub use ::tonic_crate as tonic;
+
+    #[cfg(feature = "serde_json")]
+    const PROBLEM_JSON: &str = "application/problem+json";
+
+    #[cfg(feature = "http")]
+    pub fn status_code(code: u16) -> http::StatusCode {
+        http::StatusCode::from_u16(code).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    #[cfg(feature = "serde_json")]
+    fn to_json_object<'a>(
+        fields: impl IntoIterator<Item = (&'a str, serde_json::Result<serde_json::Value>)>,
+    ) -> serde_json::Map<alloc::string::String, serde_json::Value> {
+        fields
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.unwrap_or_default()))
+            .collect()
+    }
+
+    // renders a problem details object of RFC 9457
+    #[cfg(feature = "serde_json")]
+    pub fn problem_details<'a>(
+        status: u16,
+        title: Option<&str>,
+        detail: &dyn core::fmt::Display,
+        extensions: impl IntoIterator<Item = (&'a str, serde_json::Result<serde_json::Value>)>,
+    ) -> Vec<u8> {
+        use alloc::string::ToString;
+
+        let mut object = to_json_object(extensions);
+        object.insert("type".into(), "about:blank".into());
+        if let Some(title) = title {
+            object.insert("title".into(), title.into());
+        }
+        object.insert("status".into(), status.into());
+        object.insert("detail".into(), detail.to_string().into());
+        serde_json::to_vec(&object).unwrap_or_default()
+    }
+
+    #[cfg(feature = "tonic")]
+    pub fn grpc_status(code: tonic::Code, message: &dyn core::fmt::Display) -> tonic::Status {
+        use alloc::string::ToString;
+
+        tonic::Status::new(code, message.to_string())
+    }
+
+    // attaches context fields as ASCII metadata, since the binary details are
+    // decoded as `google.rpc.Status` by clients
+    #[cfg(feature = "tonic")]
+    pub fn grpc_status_with_details<'a>(
+        code: tonic::Code,
+        message: &dyn core::fmt::Display,
+        details: impl IntoIterator<Item = (&'a str, serde_json::Result<serde_json::Value>)>,
+    ) -> tonic::Status {
+        use alloc::string::ToString;
+        use tonic::metadata::{AsciiMetadataValue, MetadataKey};
+
+        let mut status = grpc_status(code, message);
+        for (name, value) in to_json_object(details) {
+            // strings are attached as is, other values are rendered in JSON,
+            // and values which are not visible ASCII are skipped
+            let value = match value {
+                serde_json::Value::String(s) => s,
+                value => value.to_string(),
+            };
+            if let (Ok(key), Ok(value)) = (
+                MetadataKey::from_bytes(name.as_bytes()),
+                AsciiMetadataValue::try_from(value),
+            ) {
+                status.metadata_mut().insert(key, value);
+            }
+        }
+        status
+    }
+
+    #[cfg(feature = "axum")]
+    pub fn axum_response(status: http::StatusCode, body: Vec<u8>) -> axum_core::response::Response {
+        let mut response = axum_core::response::Response::new(body.into());
+        *response.status_mut() = status;
+        response.headers_mut().insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static(PROBLEM_JSON),
+        );
+        response
+    }
+
+    #[cfg(feature = "actix")]
+    pub fn actix_status(status: http::StatusCode) -> actix_web::http::StatusCode {
+        actix_web::http::StatusCode::from_u16(status.as_u16())
+            .unwrap_or(actix_web::http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    #[cfg(feature = "actix")]
+    pub fn actix_response(status: http::StatusCode, body: Vec<u8>) -> actix_web::HttpResponse {
+        actix_web::HttpResponse::build(actix_status(status))
+            .content_type(PROBLEM_JSON)
+            .body(body)
+    }
+}
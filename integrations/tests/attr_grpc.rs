@@ -0,0 +1,78 @@
+#![cfg(feature = "tonic")]
+#![allow(dead_code)]
+
+use tonic_crate::{Code, Status};
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "storage", grpc = Unavailable)]
+pub enum StorageError {
+    #[error("disk is full")]
+    DiskFull,
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "context", grpc = Internal, grpc_details)]
+pub enum Error {
+    #[error("`{path}` is not found")]
+    #[thisctx(grpc = NotFound)]
+    NotFound {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("request timed out after {0}s")]
+    #[thisctx(grpc = DeadlineExceeded)]
+    Timeout(u64),
+    #[error(transparent)]
+    #[thisctx(skip)]
+    Storage(StorageError),
+    #[error("internal error")]
+    Internal,
+}
+
+#[derive(Debug)]
+pub struct Handle;
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "opaque", grpc = Unknown)]
+pub enum OpaqueError {
+    #[error("handle is closed")]
+    Closed(Handle),
+    #[error("handle {0} is busy")]
+    #[thisctx(grpc = Unavailable, grpc_details)]
+    Busy(u32),
+}
+
+#[test]
+fn attr_grpc() {
+    use thisctx::IntoError;
+
+    let e = context::NotFound { path: "/file" }.into_error(std::io::ErrorKind::NotFound.into());
+    assert_eq!(e.grpc_code(), Code::NotFound);
+    let status = Status::from(e);
+    assert_eq!(status.code(), Code::NotFound);
+    assert_eq!(status.message(), "`/file` is not found");
+    assert_eq!(status.metadata().get("path").unwrap(), "/file");
+    assert!(status.details().is_empty());
+
+    let status = Status::from(context::Timeout(3u64).build());
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+    assert_eq!(status.metadata().get("0").unwrap(), "3");
+
+    let status = Status::from(context::Internal.build());
+    assert_eq!(status.code(), Code::Internal);
+    assert!(status.metadata().is_empty());
+
+    let status = Status::from(Error::Storage(storage::DiskFull.build()));
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "disk is full");
+
+    let status = Status::from(opaque::Closed(Handle).build());
+    assert_eq!(status.code(), Code::Unknown);
+    assert_eq!(status.message(), "handle is closed");
+    assert!(status.metadata().is_empty());
+
+    let status = Status::from(opaque::Busy(1u32).build());
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.metadata().get("0").unwrap(), "1");
+}
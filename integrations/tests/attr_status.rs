@@ -0,0 +1,134 @@
+#![cfg(feature = "http")]
+#![allow(dead_code)]
+
+use http::StatusCode;
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "storage", status = 503)]
+pub enum StorageError {
+    #[error("disk is full")]
+    DiskFull,
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "context", status = 500)]
+#[cfg_attr(feature = "axum", thisctx(response(axum)))]
+#[cfg_attr(feature = "actix", thisctx(response(actix)))]
+pub enum Error {
+    #[error("`{path}` is not found")]
+    #[thisctx(status = 404)]
+    NotFound {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("request timed out after {0}s")]
+    #[thisctx(status = 408)]
+    Timeout(u64),
+    #[error(transparent)]
+    #[thisctx(skip)]
+    Storage(StorageError),
+    #[error("internal error")]
+    Internal,
+}
+
+fn not_found() -> Error {
+    use thisctx::IntoError;
+
+    context::NotFound { path: "/file" }.into_error(std::io::ErrorKind::NotFound.into())
+}
+
+#[test]
+fn attr_status() {
+    use thisctx::IntoError;
+
+    assert_eq!(not_found().status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        context::Timeout(3u64).build().status(),
+        StatusCode::REQUEST_TIMEOUT
+    );
+    assert_eq!(
+        context::Internal.build().status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+    assert_eq!(
+        Error::Storage(storage::DiskFull.build()).status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+#[cfg(feature = "axum")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Arc::new(NoopWaker).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+#[cfg(feature = "axum")]
+#[test]
+fn attr_status_axum() {
+    use axum_core::extract::FromRequest;
+    use axum_core::response::IntoResponse;
+
+    let response = not_found().into_response();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.headers()[http::header::CONTENT_TYPE],
+        "application/problem+json"
+    );
+    let request = http::Request::new(response.into_body());
+    let body = block_on(String::from_request(request, &())).unwrap();
+    let body = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "type": "about:blank",
+            "title": "Not Found",
+            "status": 404,
+            "detail": "`/file` is not found",
+            "path": "/file",
+        })
+    );
+}
+
+#[cfg(feature = "actix")]
+#[test]
+fn attr_status_actix() {
+    use actix_web::{test, web, App};
+
+    async fn handler() -> Result<&'static str, Error> {
+        Err(thisctx::IntoError::build(context::Timeout(3u64)))
+    }
+
+    actix_web::rt::System::new().block_on(async {
+        let app = test::init_service(App::new().route("/", web::get().to(handler))).await;
+        let response = test::call_service(&app, test::TestRequest::get().to_request()).await;
+        assert_eq!(response.status().as_u16(), 408);
+        let body = test::read_body(response).await;
+        let body = serde_json::from_slice::<serde_json::Value>(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "type": "about:blank",
+                "title": "Request Timeout",
+                "status": 408,
+                "detail": "request timed out after 3s",
+                "0": 3,
+            })
+        );
+    });
+}
@@ -4,31 +4,137 @@
 //! # 🚩 Minimum supported Rust version
 //!
 //! All tests passed with `rustc v1.56`, earlier versions may not compile.
+//! Optional features are not covered.
+//!
+//! # 🧩 Optional features
+//!
+//! - `std`: `#[thisctx(exit_code = <code>)]` generates `exit_code()` and
+//!   implements `Termination`, return [`Exit`] from `main` to use them,
+//!   variants without a code exit with `EX_SOFTWARE`. Requires `rustc v1.61` or
+//!   later.
+//!
+//! # 🌐 Integrations
+//!
+//! Integrations with web frameworks are provided by the crate
+//! `thisctx_integrations`, add it as a dependency with the following features
+//! to use the corresponding attributes:
+//!
+//! - `http`: `#[thisctx(status = <code>)]` generates `status()`, which returns
+//!   an `http::StatusCode`.
+//! - `axum`: `#[thisctx(response(axum))]` implements `IntoResponse` with a
+//!   problem details body of [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457).
+//! - `actix`: `#[thisctx(response(actix))]` implements `ResponseError` with the
+//!   same body.
+//! - `tonic`: `#[thisctx(grpc = <code>)]` converts errors into a
+//!   `tonic::Status`, add `#[thisctx(grpc_details)]` to attach the context
+//!   fields as metadata.
+//!
+//! Its MSRV follows the versions supported by the dependencies of the enabled
+//! features.
 #![no_std]
 
-pub use thisctx_impl::WithContext;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub use thisctx_impl::{IntoError, WithContext};
 
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub struct NoneSource;
 
-pub trait IntoError: Sized {
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Unset;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ErrorCode {
+    Number(u32),
+    Name(&'static str),
+}
+
+impl core::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Number(n) => n.fmt(f),
+            Self::Name(s) => s.fmt(f),
+        }
+    }
+}
+
+/// Wraps the result of `main` to exit with the code reported by the error.
+///
+/// `Result<(), E>` returned from `main` always exits with `1` after printing
+/// the `Debug` output of `E`, return `Exit<E>` instead, e.g.
+/// `fn main() -> Exit<Error> { run().into() }`, to print `E` with `Display`
+/// and exit with the code of `#[thisctx(exit_code)]`.
+///
+/// Requires `rustc v1.61` or later.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Exit<E>(pub Result<(), E>);
+
+#[cfg(feature = "std")]
+impl<E> From<Result<(), E>> for Exit<E> {
+    fn from(result: Result<(), E>) -> Self {
+        Self(result)
+    }
+}
+
+#[cfg(feature = "std")]
+#[allow(clippy::incompatible_msrv)]
+impl<E: std::process::Termination> std::process::Termination for Exit<E> {
+    fn report(self) -> std::process::ExitCode {
+        match self.0 {
+            Ok(()) => std::process::ExitCode::SUCCESS,
+            Err(e) => e.report(),
+        }
+    }
+}
+
+pub trait ErrorContext {
     type Target;
     type Source;
+}
 
-    fn into_error(self, source: Self::Source) -> Self::Target;
+pub trait IntoError<S = <Self as ErrorContext>::Source>: ErrorContext + Sized {
+    fn into_error(self, source: S) -> Self::Target;
 
     fn build(self) -> Self::Target
     where
-        Self: IntoError<Source = NoneSource>,
+        Self: IntoError<NoneSource>,
     {
-        self.into_error(NoneSource)
+        IntoError::<NoneSource>::into_error(self, NoneSource)
     }
 
     fn fail<T>(self) -> Result<T, Self::Target>
     where
-        Self: IntoError<Source = NoneSource>,
+        Self: IntoError<NoneSource>,
     {
-        Err(self.build())
+        Err(IntoError::<NoneSource>::build(self))
+    }
+}
+
+/// Converts a context into one of its targets, e.g. the remote types and the
+/// groups of a variant, or [`ErrorContext::Target`].
+pub trait IntoErrorFor<T>: IntoError {
+    fn into_error_for(self, source: Self::Source) -> T;
+}
+
+impl<C: IntoError> IntoErrorFor<C::Target> for C {
+    fn into_error_for(self, source: Self::Source) -> C::Target {
+        self.into_error(source)
+    }
+}
+
+// uses `IntoErrorFor<T>` as `IntoError` targeting `T`
+struct ForTarget<C, T>(C, core::marker::PhantomData<T>);
+
+impl<C: IntoErrorFor<T>, T> ErrorContext for ForTarget<C, T> {
+    type Source = C::Source;
+    type Target = T;
+}
+
+impl<C: IntoErrorFor<T>, T> IntoError for ForTarget<C, T> {
+    fn into_error(self, source: Self::Source) -> T {
+        self.0.into_error_for(source)
     }
 }
 
@@ -67,6 +173,16 @@ pub trait WithContext: Sized {
         C: IntoError,
         Self::Err: Into<C::Source>;
 
+    /// Like [`context`](Self::context), but the target is inferred, e.g.
+    /// from the return type, instead of being [`ErrorContext::Target`].
+    fn context_for<C, T>(self, context: C) -> Result<Self::Ok, T>
+    where
+        C: IntoErrorFor<T>,
+        Self::Err: Into<C::Source>,
+    {
+        self.context_with(|| ForTarget(context, core::marker::PhantomData))
+    }
+
     fn provide<C>(self, value: impl Into<C>) -> Self
     where
         Self::Err: WithOptional<C>,
@@ -125,9 +241,32 @@ impl<T> WithContext for Option<T> {
 /// **NOT PUBLIC APIS**
 #[doc(hidden)]
 pub mod private {
-    pub use core::convert::{From, Into};
+    pub use core::convert::{identity, From, Into, TryFrom};
     pub use core::default::Default;
+    pub use core::marker::PhantomData;
     pub use core::option::Option;
+    pub use core::result::Result::{self, Err, Ok};
+
+    #[cfg(feature = "std")]
+    pub use ::std::eprintln;
+    #[cfg(feature = "std")]
+    pub use ::std::process::{ExitCode, Termination};
 
     pub use super::*;
+
+    // builds a struct flattened by `#[thisctx(flatten(<fields>))]` from the
+    // values of its context fields
+    pub trait Flatten<Fields> {
+        fn flatten(fields: Fields) -> Self;
+    }
+
+    // the position of a field in `Flatten<Fields>`
+    #[derive(Debug, Clone, Copy)]
+    pub struct FlattenIndex<const N: usize>;
+
+    // helps to infer the argument type of `#[thisctx(from(when = <closure>))]`
+    #[inline]
+    pub fn when<T>(value: &T, f: impl FnOnce(&T) -> bool) -> bool {
+        f(value)
+    }
 }
@@ -0,0 +1,79 @@
+#![allow(dead_code)]
+
+use std::io;
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(accessors, module = "context")]
+pub enum Error {
+    Io {
+        source: io::Error,
+        path: String,
+    },
+    Timeout(#[source] io::Error, u64),
+    NotFound {
+        key: String,
+    },
+    Unit,
+    #[thisctx(accessors = false)]
+    Internal,
+    #[thisctx(skip)]
+    Skipped {
+        code: u16,
+    },
+    Nested {
+        source: Box<Self>,
+    },
+}
+
+#[test]
+fn attr_accessors() {
+    use thisctx::IntoError;
+
+    let e = context::Io { path: "/file" }.into_error(io::Error::from(io::ErrorKind::NotFound));
+    assert!(e.is_io());
+    assert!(!e.is_not_found());
+    assert_eq!(e.source_of_io().unwrap().kind(), io::ErrorKind::NotFound);
+    assert!(e.source_of_timeout().is_none());
+    let context::IoRef { source, path } = e.as_io().unwrap();
+    assert_eq!(path, "/file");
+    assert_eq!(source.kind(), io::ErrorKind::NotFound);
+    assert!(e.as_not_found().is_none());
+
+    let e = context::Timeout(30u64).into_error(io::Error::from(io::ErrorKind::TimedOut));
+    let context::TimeoutRef(_, secs) = e.as_timeout().unwrap();
+    assert_eq!(*secs, 30);
+
+    assert!(context::Unit.build().is_unit());
+
+    // skipped variants have accessors as well
+    let e = Error::Skipped { code: 1 };
+    assert!(e.is_skipped());
+    let context::SkippedRef { code } = e.as_skipped().unwrap();
+    assert_eq!(*code, 1);
+
+    let e = context::Nested.into_error(context::Unit.build());
+    let context::NestedRef { source } = e.as_nested().unwrap();
+    assert!(source.is_unit());
+    assert!(e.source_of_nested().unwrap().is_unit());
+}
+
+// contexts are skipped, they cannot leave parameters of the enum unused
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(accessors, skip, module = "generic")]
+pub enum GenericError<'a, T> {
+    Value { value: T },
+    Name(&'a str),
+    Code(u8),
+}
+
+#[test]
+fn attr_accessors_generic() {
+    let e: GenericError<'_, i32> = GenericError::Value { value: 1 };
+    let generic::ValueRef { value, .. } = e.as_value().unwrap();
+    assert_eq!(*value, 1);
+
+    let e: GenericError<'_, i32> = GenericError::Code(2);
+    let generic::CodeRef(code, _) = e.as_code().unwrap();
+    assert_eq!(*code, 2);
+    assert!(e.as_name().is_none());
+}
@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+
+use thisctx::{IntoError, WithContext};
+
+#[derive(Debug, Eq, PartialEq, WithContext)]
+#[thisctx(builder)]
+enum Error {
+    Request {
+        method: String,
+        url: String,
+        status: u16,
+        source: &'static str,
+    },
+    Unnamed(String, i32),
+    #[thisctx(builder = false)]
+    NoBuilder(i32),
+}
+
+#[derive(Debug, Eq, PartialEq, WithContext)]
+#[thisctx(builder, suffix = "Context")]
+struct Generic<'a, T: Clone>(&'a str, T);
+
+#[test]
+fn attr_builder() {
+    assert_eq!(
+        Request::builder()
+            .url("/index.html")
+            .method("GET")
+            .status(404)
+            .into_error("not found"),
+        Error::Request {
+            method: "GET".to_owned(),
+            url: "/index.html".to_owned(),
+            status: 404,
+            source: "not found",
+        },
+    );
+    assert_eq!(
+        Err::<(), _>("timed out").context(
+            Request::builder()
+                .method("POST")
+                .url("/login".to_owned())
+                .status(504),
+        ),
+        Err(Error::Request {
+            method: "POST".to_owned(),
+            url: "/login".to_owned(),
+            status: 504,
+            source: "timed out",
+        }),
+    );
+    assert_eq!(
+        Unnamed::builder()._1(1)._0("anyhow").build(),
+        Error::Unnamed("anyhow".to_owned(), 1),
+    );
+
+    let context = Unnamed::builder()._0("anyhow")._1(2).build_context();
+    assert_eq!(context.0, "anyhow");
+    assert_eq!(context.1, 2);
+
+    assert_eq!(
+        GenericContext::builder()._0("anyhow")._1(3).build(),
+        Generic("anyhow", 3),
+    );
+}
@@ -0,0 +1,58 @@
+#![allow(dead_code)]
+
+use thisctx::ErrorCode;
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "storage")]
+pub enum StorageError {
+    #[error("disk is full")]
+    #[thisctx(code = 2001)]
+    DiskFull,
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "context")]
+pub enum Error {
+    #[error("`{path}` is not found")]
+    #[thisctx(code = 1001)]
+    NotFound { path: String },
+    #[error("timeout")]
+    #[thisctx(code = "E_TIMEOUT")]
+    Timeout(u64),
+    #[error(transparent)]
+    #[thisctx(skip)]
+    Storage(StorageError),
+    #[error("internal")]
+    #[thisctx(skip, code = 1000)]
+    Internal,
+}
+
+#[test]
+fn attr_code() {
+    use thisctx::IntoError;
+
+    let e = context::NotFound { path: "/file" }.build();
+    assert_eq!(e.code(), ErrorCode::Number(1001));
+    assert_eq!(
+        context::Timeout(3u64).build().code(),
+        ErrorCode::Name("E_TIMEOUT")
+    );
+    assert_eq!(Error::Internal.code(), ErrorCode::Number(1000));
+    assert_eq!(
+        Error::Storage(storage::DiskFull.build()).code(),
+        ErrorCode::Number(2001)
+    );
+    assert_eq!(ErrorCode::Name("E_TIMEOUT").to_string(), "E_TIMEOUT");
+    assert_eq!(ErrorCode::Number(1001).to_string(), "1001");
+
+    assert_eq!(Error::CODES.len(), 3);
+    assert_eq!(
+        Error::variant_name_of_code(ErrorCode::Number(1001)),
+        Some("NotFound")
+    );
+    assert_eq!(
+        Error::variant_name_of_code(ErrorCode::Name("E_TIMEOUT")),
+        Some("Timeout")
+    );
+    assert_eq!(Error::variant_name_of_code(ErrorCode::Number(2001)), None);
+}
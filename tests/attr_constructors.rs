@@ -0,0 +1,59 @@
+#![allow(dead_code)]
+
+#[derive(Debug, Eq, PartialEq, thisctx::WithContext)]
+#[thisctx(constructors, module = "context")]
+enum Error {
+    NotFound {
+        path: String,
+    },
+    Io {
+        path: String,
+        source: &'static str,
+    },
+    Unnamed(String, i32),
+    HTTPRequest(#[source] &'static str),
+    Type,
+    Read(*const u8),
+    Write(*const u8, usize),
+    #[thisctx(constructors = false)]
+    NoConstructor,
+}
+
+impl Error {
+    fn no_constructor() -> Self {
+        Error::NoConstructor
+    }
+}
+
+#[test]
+fn attr_constructors() {
+    assert_eq!(
+        Error::not_found("/tmp"),
+        Error::NotFound {
+            path: "/tmp".to_owned(),
+        },
+    );
+    assert_eq!(
+        Error::io("blah", "/tmp"),
+        Error::Io {
+            path: "/tmp".to_owned(),
+            source: "blah",
+        },
+    );
+    assert_eq!(
+        Error::unnamed("anyhow", 1),
+        Error::Unnamed("anyhow".to_owned(), 1),
+    );
+    assert_eq!(
+        Error::http_request("timed out"),
+        Error::HTTPRequest("timed out"),
+    );
+    assert_eq!(Error::r#type(), Error::Type);
+    assert_eq!(Error::no_constructor(), Error::NoConstructor);
+    let buf = [0u8; 4];
+    assert_eq!(Error::read(buf.as_ptr()), Error::Read(buf.as_ptr()));
+    assert_eq!(
+        Error::write(buf.as_ptr(), 4usize),
+        Error::Write(buf.as_ptr(), 4),
+    );
+}
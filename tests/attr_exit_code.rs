@@ -0,0 +1,68 @@
+#![cfg(feature = "std")]
+#![allow(dead_code)]
+
+use std::process::{ExitCode, Termination};
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "config", exit_code = EX_CONFIG)]
+pub enum ConfigError {
+    #[error("missing key `{0}`")]
+    MissingKey(String),
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "io")]
+pub enum IoError {
+    #[error("cannot open `{0}`")]
+    #[thisctx(exit_code = EX_NOINPUT)]
+    Open(String),
+    #[error("cannot write")]
+    Write,
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(module = "context", exit_code = 1)]
+pub enum Error {
+    #[error("invalid argument `{0}`")]
+    #[thisctx(exit_code = EX_USAGE)]
+    InvalidArgument(String),
+    #[error("interrupted")]
+    #[thisctx(exit_code = 130)]
+    Interrupted,
+    #[error(transparent)]
+    #[thisctx(skip)]
+    Config(ConfigError),
+    #[error("failed")]
+    Failed,
+}
+
+fn assert_code(code: ExitCode, expected: u8) {
+    assert_eq!(
+        format!("{:?}", code),
+        format!("{:?}", ExitCode::from(expected))
+    );
+}
+
+#[test]
+fn attr_exit_code() {
+    use thisctx::IntoError;
+
+    assert_code(context::InvalidArgument("-x").build().exit_code(), 64);
+    assert_code(context::Interrupted.build().exit_code(), 130);
+    assert_code(context::Failed.build().exit_code(), 1);
+    let e = Error::Config(config::MissingKey("name").build());
+    assert_code(e.exit_code(), 78);
+    assert_code(e.report(), 78);
+    assert_code(io::Open("a.txt").build().exit_code(), 66);
+    assert_code(io::Write.build().exit_code(), 70);
+}
+
+#[test]
+fn attr_exit_code_main() {
+    use thisctx::{Exit, IntoError};
+
+    let exit = Exit::<Error>::from(Ok(()));
+    assert_code(exit.report(), 0);
+    let exit = Exit::from(Err(context::Interrupted.build()));
+    assert_code(exit.report(), 130);
+}
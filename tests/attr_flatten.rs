@@ -0,0 +1,80 @@
+#![allow(dead_code)]
+
+use thisctx::{IntoError, WithContext};
+
+#[derive(Debug, Eq, PartialEq, WithContext)]
+#[thisctx(suffix = "Context")]
+struct RequestInfo {
+    method: String,
+    url: String,
+    #[thisctx(magic = false)]
+    retries: u8,
+}
+
+#[derive(Debug, Eq, PartialEq, WithContext)]
+#[thisctx(builder)]
+enum Error {
+    Status {
+        #[thisctx(flatten(method, url, retries))]
+        request: RequestInfo,
+        status: u16,
+    },
+    Timeout {
+        #[thisctx(flatten(method, url, retries))]
+        request: RequestInfo,
+        #[source]
+        reason: &'static str,
+    },
+}
+
+#[test]
+fn attr_flatten() {
+    assert_eq!(
+        Status {
+            method: "GET",
+            url: "/index.html",
+            retries: 0,
+            status: 404u16,
+        }
+        .build(),
+        Error::Status {
+            request: RequestInfo {
+                method: "GET".to_owned(),
+                url: "/index.html".to_owned(),
+                retries: 0,
+            },
+            status: 404,
+        },
+    );
+    assert_eq!(
+        Timeout {
+            method: "POST",
+            url: "/login".to_owned(),
+            retries: 3,
+        }
+        .into_error("timed out"),
+        Error::Timeout {
+            request: RequestInfo {
+                method: "POST".to_owned(),
+                url: "/login".to_owned(),
+                retries: 3,
+            },
+            reason: "timed out",
+        },
+    );
+    assert_eq!(
+        Timeout::builder()
+            .method("PUT")
+            .url("/upload")
+            .retries(1)
+            .into_error("timed out"),
+        Error::Timeout {
+            request: RequestInfo {
+                method: "PUT".to_owned(),
+                url: "/upload".to_owned(),
+                retries: 1,
+            },
+            reason: "timed out",
+        },
+    );
+}
@@ -1,5 +1,8 @@
 #![allow(dead_code)]
 
+use std::error::Error as StdError;
+use std::{fmt, io};
+
 struct Remote;
 
 #[derive(thisctx::WithContext)]
@@ -15,5 +18,96 @@ impl From<Error> for Remote {
     }
 }
 
+#[derive(Debug, thisctx::WithContext)]
+enum Toggled {
+    On(#[thisctx(from = true)] u8),
+    Off(#[thisctx(from = false)] u8),
+}
+
+#[test]
+fn attr_from_bool() {
+    assert!(matches!(Toggled::from(1u8), Toggled::On(1)));
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum Internal {
+    Any(#[thisctx(from(io::Error, fmt::Error))] Box<dyn StdError + Send + Sync>),
+}
+
 #[test]
 fn attr_module() {}
+
+#[test]
+fn attr_from_types() {
+    let Internal::Any(e) = Internal::from(fmt::Error);
+    assert!(e.is::<fmt::Error>());
+    let Internal::Any(e) = Internal::from(io::Error::from(io::ErrorKind::NotFound));
+    assert!(e.is::<io::Error>());
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum IoError {
+    Io {
+        #[thisctx(from)]
+        source: io::Error,
+        #[thisctx(optional)]
+        path: Option<String>,
+        #[thisctx(default)]
+        retryable: bool,
+        #[thisctx(default = "3")]
+        attempts: u32,
+    },
+}
+
+#[test]
+fn attr_from_with_defaults() {
+    use thisctx::IntoError;
+
+    let IoError::Io {
+        path,
+        retryable,
+        attempts,
+        ..
+    } = IoError::from(io::Error::from(io::ErrorKind::NotFound));
+    assert_eq!(path, None);
+    assert!(!retryable);
+    assert_eq!(attempts, 3);
+
+    let IoError::Io {
+        retryable,
+        attempts,
+        ..
+    } = Io {
+        retryable: true,
+        attempts: 1u32,
+    }
+    .into_error(io::Error::from(io::ErrorKind::NotFound));
+    assert!(retryable);
+    assert_eq!(attempts, 1);
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum FsError {
+    NotFound(#[thisctx(from(when = |e| e.kind() == io::ErrorKind::NotFound))] io::Error),
+    PermissionDenied(
+        #[thisctx(from(io::Error, when = |e: &io::Error| e.kind() == io::ErrorKind::PermissionDenied))]
+         io::Error,
+    ),
+    Other(#[thisctx(from)] io::Error),
+}
+
+#[test]
+fn attr_from_when() {
+    assert!(matches!(
+        FsError::from(io::Error::from(io::ErrorKind::NotFound)),
+        FsError::NotFound(_)
+    ));
+    assert!(matches!(
+        FsError::from(io::Error::from(io::ErrorKind::PermissionDenied)),
+        FsError::PermissionDenied(_)
+    ));
+    assert!(matches!(
+        FsError::from(io::Error::from(io::ErrorKind::Interrupted)),
+        FsError::Other(_)
+    ));
+}
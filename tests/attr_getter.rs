@@ -0,0 +1,71 @@
+#![allow(dead_code)]
+
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug, thisctx::WithContext)]
+enum Error {
+    Read {
+        #[thisctx(getter)]
+        request_id: u64,
+        path: PathBuf,
+    },
+    Write {
+        request_id: u64,
+        path: PathBuf,
+        size: usize,
+    },
+}
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(getter, suffix = Context)]
+enum SharedError {
+    Read { path: PathBuf, offset: u64 },
+    Write { path: PathBuf, offset: usize },
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(getter, module = "io_error")]
+pub enum IoError {
+    #[error("failed to read {}", path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("failed to write {}", path.display())]
+    Write { path: PathBuf, source: io::Error },
+}
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(suffix = Context)]
+struct StructError {
+    #[thisctx(getter)]
+    path: PathBuf,
+}
+
+#[test]
+fn attr_getter() {
+    use std::error::Error as _;
+
+    use thisctx::IntoError;
+
+    let e = Write {
+        request_id: 1u64,
+        path: "/file",
+        size: 0usize,
+    }
+    .build();
+    assert_eq!(*e.request_id(), 1);
+
+    let e = WriteContext {
+        path: "/file",
+        offset: 0usize,
+    }
+    .build();
+    assert_eq!(e.path(), &PathBuf::from("/file"));
+
+    let e = StructErrorContext { path: "/file" }.build();
+    assert_eq!(e.path(), &PathBuf::from("/file"));
+
+    // `source` is not detected, which would shadow `Error::source`
+    let e = io_error::Read { path: "/file" }.into_error(io::ErrorKind::NotFound.into());
+    assert_eq!(e.path(), &PathBuf::from("/file"));
+    assert!(e.source().is_some());
+}
@@ -0,0 +1,78 @@
+#![allow(dead_code)]
+
+use std::convert::TryFrom;
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+#[thisctx(group_attr(derive(Debug, thiserror::Error)))]
+enum Error {
+    #[error("unexpected token {token} at line {line}")]
+    #[thisctx(group = Parse)]
+    UnexpectedToken { line: usize, token: String },
+    #[error("unexpected end of file at line {0}")]
+    #[thisctx(group = Parse, group = Syntax)]
+    UnexpectedEof(#[thisctx(magic = false)] usize),
+    #[error("io error")]
+    #[thisctx(group = Io)]
+    Io(#[source] std::io::Error),
+    #[error("in `{name}`")]
+    #[thisctx(group = Parse)]
+    InModule { name: String, source: Box<Self> },
+    #[error("unknown error")]
+    Unknown,
+}
+
+fn parse(input: &str) -> Result<(), ParseError> {
+    use thisctx::WithContext;
+
+    if input.is_empty() {
+        return None.context_for(UnexpectedEof(0));
+    }
+    None.context_for(UnexpectedToken {
+        line: 1,
+        token: input,
+    })
+}
+
+#[test]
+fn attr_group() {
+    assert!(matches!(parse(""), Err(ParseError::UnexpectedEof(0))));
+    assert!(matches!(
+        Error::from(parse("x").unwrap_err()),
+        Error::UnexpectedToken { line: 1, ref token } if token == "x"
+    ));
+    assert!(matches!(
+        ParseError::try_from(Error::UnexpectedEof(2)),
+        Ok(ParseError::UnexpectedEof(2))
+    ));
+    assert!(matches!(
+        SyntaxError::try_from(Error::Unknown),
+        Err(Error::Unknown)
+    ));
+    let e = ParseError::try_from(Error::InModule {
+        name: "main".to_owned(),
+        source: Box::new(Error::Unknown),
+    });
+    assert!(matches!(
+        e,
+        Ok(ParseError::InModule { ref source, .. }) if matches!(**source, Error::Unknown)
+    ));
+    let e = IoError::Io(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(std::error::Error::source(&e).is_some());
+    assert!(matches!(Error::from(e), Error::Io(_)));
+}
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+enum PlainError {
+    #[error("io error")]
+    #[thisctx(group = Read)]
+    Read(#[source] std::io::Error),
+    #[error("closed")]
+    Closed,
+}
+
+#[test]
+fn attr_group_without_attrs() {
+    // thiserror attributes are stripped from the grouped enum
+    let e = ReadPlainError::Read(std::io::Error::from(std::io::ErrorKind::NotFound));
+    assert!(matches!(PlainError::from(e), PlainError::Read(_)));
+}
@@ -0,0 +1,37 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(kind, module = "context")]
+pub enum Error {
+    NotFound {
+        path: String,
+    },
+    Timeout(u64),
+    #[thisctx(skip)]
+    Internal,
+    r#Type,
+}
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(kind = StatusKind, suffix = Context)]
+enum Status {}
+
+#[test]
+fn attr_kind() {
+    use thisctx::IntoError;
+
+    let e = context::NotFound { path: "/file" }.build();
+    assert_eq!(e.kind(), ErrorKind::NotFound);
+    assert_eq!(e.variant_name(), "NotFound");
+    assert_eq!(Error::Internal.kind(), ErrorKind::Internal);
+    assert_eq!(context::Type.build().variant_name(), "Type");
+    assert_eq!(ErrorKind::Timeout.variant_name(), "Timeout");
+
+    let mut counts = HashMap::new();
+    *counts.entry(e.kind()).or_insert(0) += 1;
+    assert_eq!(counts[&ErrorKind::NotFound], 1);
+
+    let _: fn(&Status) -> StatusKind = Status::kind;
+}
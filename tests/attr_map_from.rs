@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+mod storage {
+    #[derive(Debug)]
+    pub enum Error {
+        NotFound { key: &'static str },
+        Corrupted(&'static str, u64),
+        Timeout,
+    }
+}
+
+mod cache {
+    #[derive(Debug)]
+    pub enum Error {
+        NotFound { key: &'static str },
+        Corrupted(&'static str, u64),
+        Timeout,
+    }
+}
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(skip, map_from = storage::Error, map_from = cache::Error)]
+enum Error {
+    NotFound {
+        key: String,
+    },
+    #[thisctx(map = Corrupted)]
+    Invalid(String, u64),
+    Timeout,
+    #[thisctx(map = false)]
+    Internal,
+}
+
+#[test]
+fn attr_map_from() {
+    assert!(matches!(
+        Error::from(storage::Error::NotFound { key: "k" }),
+        Error::NotFound { ref key } if key == "k"
+    ));
+    assert!(matches!(
+        Error::from(storage::Error::Corrupted("block", 42)),
+        Error::Invalid(ref s, 42) if s == "block"
+    ));
+    assert!(matches!(Error::from(cache::Error::Timeout), Error::Timeout));
+    assert!(matches!(
+        Error::from(cache::Error::Corrupted("k", 1)),
+        Error::Invalid(_, 1)
+    ));
+}
@@ -0,0 +1,41 @@
+#![allow(dead_code)]
+
+mod upstream {
+    #[derive(Debug)]
+    pub enum Error {
+        Io {
+            source: std::io::Error,
+            path: String,
+        },
+        Parse(String, usize),
+        Unknown,
+    }
+}
+
+#[derive(thisctx::WithContext)]
+#[thisctx(mirror = upstream::Error)]
+enum Error {
+    Io {
+        source: std::io::Error,
+        path: String,
+    },
+    Parse(String, usize),
+    Unknown,
+}
+
+#[test]
+fn attr_mirror() {
+    use std::io;
+
+    use thisctx::{IntoError, WithContext};
+
+    assert!(matches!(
+        Err::<(), _>(io::Error::from(io::ErrorKind::NotFound)).context(Io { path: "/file" }),
+        Err(upstream::Error::Io { ref path, .. }) if path == "/file"
+    ));
+    assert!(matches!(
+        Parse("invalid", 1usize).build(),
+        upstream::Error::Parse(ref s, 1) if s == "invalid"
+    ));
+    assert!(matches!(Unknown.build(), upstream::Error::Unknown));
+}
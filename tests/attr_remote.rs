@@ -0,0 +1,95 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(remote(LibError, CliError))]
+enum Error {
+    ReadConfig { path: PathBuf },
+}
+
+#[derive(Debug)]
+struct LibError(Error);
+
+impl From<Error> for LibError {
+    fn from(e: Error) -> Self {
+        Self(e)
+    }
+}
+
+#[derive(Debug)]
+struct CliError {
+    message: String,
+}
+
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        Self {
+            message: format!("{:?}", e),
+        }
+    }
+}
+
+fn lib() -> Result<(), LibError> {
+    use thisctx::WithContext;
+
+    None.context_for(ReadConfig { path: "lib.toml" })
+}
+
+fn cli() -> Result<(), CliError> {
+    use thisctx::WithContext;
+
+    None.context_for(ReadConfig { path: "cli.toml" })
+}
+
+#[test]
+fn attr_remote() {
+    use thisctx::IntoError;
+
+    assert!(matches!(
+        lib(),
+        Err(LibError(Error::ReadConfig { ref path })) if path == &PathBuf::from("lib.toml")
+    ));
+    assert!(cli().unwrap_err().message.contains("cli.toml"));
+    // the first remote type is the target
+    let LibError(_) = ReadConfig { path: "" }.build();
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum LocalError {
+    Missing { path: PathBuf },
+}
+
+// implementors only need the required methods
+struct Checked(Option<u8>);
+
+impl thisctx::WithContext for Checked {
+    type Err = thisctx::NoneSource;
+    type Ok = u8;
+
+    fn context_with<C>(self, f: impl FnOnce() -> C) -> Result<u8, C::Target>
+    where
+        C: thisctx::IntoError,
+        thisctx::NoneSource: Into<C::Source>,
+    {
+        self.0.context_with(f)
+    }
+
+    fn provide_with<C>(self, _: impl FnOnce() -> C) -> Self
+    where
+        thisctx::NoneSource: thisctx::WithOptional<C>,
+    {
+        self
+    }
+}
+
+#[test]
+fn attr_remote_context_for() {
+    use thisctx::WithContext;
+
+    // the target of a context is always accepted
+    let e: Result<(), LocalError> = None.context_for(Missing { path: "local.toml" });
+    assert!(matches!(e, Err(LocalError::Missing { .. })));
+    let e: Result<u8, CliError> = Checked(None).context_for(ReadConfig { path: "cli.toml" });
+    assert!(e.unwrap_err().message.contains("cli.toml"));
+}
@@ -9,6 +9,10 @@ enum Error {
     WithSuffix,
     #[thisctx(rename = "ErrorWithRenameContext")]
     WithRename,
+    WithRenamedField {
+        #[thisctx(rename = "path")]
+        src_path: String,
+    },
 }
 
 #[test]
@@ -17,3 +21,11 @@ fn attr_suffix() {
     let _ = WithSuffixContext;
     let _ = ErrorWithRenameContext;
 }
+
+#[test]
+fn attr_rename_field() {
+    use thisctx::IntoError;
+
+    let e = WithRenamedField { path: "/tmp" }.build();
+    assert!(matches!(e, Error::WithRenamedField { src_path } if src_path == "/tmp"));
+}
@@ -0,0 +1,44 @@
+#![allow(dead_code)]
+
+use thisctx::IntoError;
+
+mod error {
+    #[derive(Debug, Eq, PartialEq, thisctx::WithContext)]
+    #[thisctx(sealed)]
+    pub enum Error {
+        Named {
+            path: String,
+            code: i32,
+        },
+        Unnamed(String, #[source] &'static str),
+        Unit,
+        #[thisctx(sealed = false)]
+        NotSealed {
+            path: String,
+        },
+    }
+}
+
+use error::Error;
+
+#[test]
+fn attr_sealed() {
+    assert_eq!(
+        error::Named::new("/tmp", 1).build(),
+        Error::Named {
+            path: "/tmp".to_owned(),
+            code: 1,
+        },
+    );
+    assert_eq!(
+        error::Unnamed::new("anyhow").into_error("blah"),
+        Error::Unnamed("anyhow".to_owned(), "blah"),
+    );
+    assert_eq!(error::Unit::new().build(), Error::Unit);
+    assert_eq!(
+        error::NotSealed { path: "/tmp" }.build(),
+        Error::NotSealed {
+            path: "/tmp".to_owned(),
+        },
+    );
+}
@@ -0,0 +1,170 @@
+#![allow(dead_code)]
+
+use std::error::Error as StdError;
+use std::sync::Arc;
+use std::{fmt, io};
+
+#[derive(Debug, thisctx::WithContext)]
+enum Error {
+    Any {
+        #[thisctx(source(generic))]
+        source: Box<dyn StdError + Send + Sync>,
+        context: String,
+    },
+    Message(#[thisctx(source(generic))] String, i32),
+}
+
+fn wrap<C, S>(context: C, source: S) -> C::Target
+where
+    C: thisctx::IntoError<S>,
+{
+    context.into_error(source)
+}
+
+#[test]
+fn attr_source_generic() {
+    use thisctx::{IntoError, WithContext};
+
+    let e = Any { context: "io" }.into_error(io::Error::from(io::ErrorKind::NotFound));
+    match e {
+        Error::Any { source, .. } => assert!(source.is::<io::Error>()),
+        _ => unreachable!(),
+    }
+    assert!(matches!(
+        Any { context: "fmt" }.into_error(fmt::Error),
+        Error::Any { .. }
+    ));
+    assert!(matches!(
+        Message(1).into_error("message"),
+        Error::Message(_, 1)
+    ));
+    assert!(matches!(
+        Err::<(), _>(fmt::Error).context(Any { context: "fmt" }),
+        Err(Error::Any { .. })
+    ));
+    // a single context wraps sources of different types
+    assert!(matches!(
+        wrap(
+            Any { context: "io" },
+            io::Error::from(io::ErrorKind::NotFound)
+        ),
+        Error::Any { .. }
+    ));
+    assert!(matches!(
+        wrap(Any { context: "fmt" }, fmt::Error),
+        Error::Any { .. }
+    ));
+    assert!(matches!(
+        wrap(Message(1), String::from("message")),
+        Error::Message(_, 1)
+    ));
+}
+
+#[derive(Clone, Debug, thiserror::Error, thisctx::WithContext)]
+enum SharedError {
+    #[error("failed to read {path}")]
+    Read {
+        #[thisctx(source(shared))]
+        source: Arc<io::Error>,
+        path: String,
+    },
+    #[error("io error")]
+    Io(#[thisctx(from, source(shared))] Arc<io::Error>),
+}
+
+#[test]
+fn attr_source_shared() {
+    use thisctx::WithContext;
+
+    let e = Err::<(), _>(io::Error::from(io::ErrorKind::NotFound))
+        .context(Read { path: "/file" })
+        .unwrap_err();
+    let cloned = e.clone();
+    // `Arc<E>` delegates `Display` and `Error` to the inner error
+    let source = e.source().unwrap();
+    assert!(source.is::<Arc<io::Error>>());
+    assert_eq!(
+        source.to_string(),
+        io::Error::from(io::ErrorKind::NotFound).to_string()
+    );
+    assert_eq!(cloned.source().unwrap().to_string(), source.to_string());
+
+    let e = SharedError::from(io::Error::from(io::ErrorKind::NotFound));
+    assert!(matches!(e, SharedError::Io(_)));
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum CompileError {
+    InModule {
+        name: String,
+        source: Box<Self>,
+    },
+    InFunction {
+        name: String,
+        #[source]
+        inner: Box<CompileError>,
+    },
+    Syntax {
+        line: usize,
+    },
+}
+
+fn parse(line: usize) -> Result<(), CompileError> {
+    use thisctx::IntoError;
+
+    Syntax { line }.fail()
+}
+
+fn compile_function() -> Result<(), CompileError> {
+    use thisctx::WithContext;
+
+    parse(42).context(InFunction { name: "main" })
+}
+
+fn compile_module() -> Result<(), CompileError> {
+    use thisctx::{IntoError, WithContext};
+
+    compile_function().context(InModule { name: "lib" })?;
+    Err(InModule { name: "lib" }.into_error(Syntax { line: 1 }.build()))
+}
+
+#[test]
+fn attr_source_boxed_self() {
+    match compile_module().unwrap_err() {
+        CompileError::InModule { name, source } => {
+            assert_eq!(name, "lib");
+            assert!(matches!(
+                *source,
+                CompileError::InFunction { ref inner, .. }
+                    if matches!(**inner, CompileError::Syntax { line: 42 })
+            ));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[derive(Debug, thisctx::WithContext)]
+enum NestedError {
+    Nested(#[thisctx(from)] Box<NestedError>),
+    Leaf(i32),
+}
+
+#[test]
+fn attr_source_boxed_self_from() {
+    use thisctx::{ErrorContext, IntoError};
+
+    fn source_of<C: ErrorContext>(_: &C) -> core::marker::PhantomData<C::Source> {
+        core::marker::PhantomData
+    }
+
+    // the boxed error stays the source, the unboxed one is accepted as well
+    let _: core::marker::PhantomData<Box<NestedError>> = source_of(&Nested);
+    assert!(matches!(
+        NestedError::from(Box::new(Leaf(1).build())),
+        NestedError::Nested(ref e) if matches!(**e, NestedError::Leaf(1))
+    ));
+    assert!(matches!(
+        Nested.into_error(Leaf(2).build()),
+        NestedError::Nested(ref e) if matches!(**e, NestedError::Leaf(2))
+    ));
+}
@@ -0,0 +1,67 @@
+#![allow(dead_code)]
+
+use std::io;
+
+#[derive(Debug, thiserror::Error, thisctx::WithContext)]
+enum Error {
+    #[error("io error")]
+    Io {
+        #[from]
+        original: io::Error,
+    },
+    #[error("parse error at line {line}")]
+    Parse {
+        #[source]
+        source: std::num::ParseIntError,
+        line: usize,
+    },
+    #[error("format error")]
+    Fmt(
+        #[from]
+        #[thisctx(from)]
+        std::fmt::Error,
+    ),
+}
+
+#[test]
+fn attr_thiserror_from() {
+    use thisctx::IntoError;
+
+    assert!(matches!(
+        Error::from(io::Error::from(io::ErrorKind::NotFound)),
+        Error::Io { .. }
+    ));
+    assert!(matches!(
+        Io.into_error(io::Error::from(io::ErrorKind::NotFound)),
+        Error::Io { .. }
+    ));
+    assert!(matches!(Error::from(std::fmt::Error), Error::Fmt(_)));
+}
+
+#[rustversion::since(1.65)]
+mod backtrace {
+    use std::backtrace::Backtrace;
+
+    #[derive(Debug, thisctx::WithContext)]
+    enum Error {
+        Named {
+            #[source]
+            source: &'static str,
+            #[backtrace]
+            backtrace: Backtrace,
+            context: i32,
+        },
+        Unnamed(#[backtrace] Option<Backtrace>, i32),
+    }
+
+    #[test]
+    fn attr_backtrace() {
+        use thisctx::IntoError;
+
+        assert!(matches!(
+            Named { context: 1 }.into_error("error"),
+            Error::Named { context: 1, .. }
+        ));
+        assert!(matches!(Unnamed(2).build(), Error::Unnamed(Some(_), 2)));
+    }
+}
@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+
+mod error {
+    #[derive(thisctx::WithContext)]
+    pub enum Error {
+        Named {
+            #[thisctx(vis = "pub(crate)")]
+            field: i32,
+        },
+        Unnamed(#[thisctx(vis = "pub(crate)")] i32),
+    }
+}
+
+#[test]
+fn attr_vis_field() {
+    let _ = error::Named { field: 0 };
+    let _ = error::Unnamed(0);
+}
@@ -0,0 +1,9 @@
+#[derive(thisctx::WithContext)]
+enum Error {
+    #[thisctx(code = 1001)]
+    NotFound,
+    #[thisctx(code = 1001)]
+    Timeout,
+}
+
+fn main() {}
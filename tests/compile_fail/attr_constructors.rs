@@ -0,0 +1,17 @@
+#[derive(thisctx::WithContext)]
+#[thisctx(constructors, kind)]
+enum Error {
+    Kind,
+}
+
+#[derive(thisctx::WithContext)]
+#[thisctx(constructors)]
+enum Error2 {
+    Variant {
+        #[source]
+        cause: String,
+        source: String,
+    },
+}
+
+fn main() {}
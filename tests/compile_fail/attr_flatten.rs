@@ -0,0 +1,22 @@
+#![allow(dead_code)]
+
+#[derive(thisctx::WithContext)]
+#[thisctx(suffix = "Context")]
+struct RequestInfo {
+    method: String,
+    url: String,
+}
+
+#[derive(thisctx::WithContext)]
+enum Error {
+    Reversed {
+        #[thisctx(flatten(url, method))]
+        request: RequestInfo,
+    },
+    Misspelled {
+        #[thisctx(flatten(method, uri))]
+        request: RequestInfo,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[derive(thisctx::WithContext)]
+enum Error {
+    Read {
+        #[thisctx(getter)]
+        request_id: u64,
+    },
+    Write {},
+    Delete {
+        request_id: String,
+    },
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+mod error {
+    #[derive(thisctx::WithContext)]
+    #[thisctx(sealed)]
+    pub enum Error {
+        Named { path: String },
+    }
+}
+
+fn main() {
+    let _ = error::Named { path: "/tmp" };
+}
@@ -0,0 +1,12 @@
+mod error {
+    #[derive(thisctx::WithContext)]
+    #[thisctx(sealed)]
+    pub enum Error {
+        Named { path: String },
+    }
+}
+
+fn main() {
+    let context = error::Named::new("/tmp");
+    let _ = context.path;
+}
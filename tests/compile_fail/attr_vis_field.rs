@@ -0,0 +1,18 @@
+mod error {
+    #[derive(thisctx::WithContext)]
+    pub enum Error {
+        Named {
+            #[thisctx(vis = "pub(crate)")]
+            visible: i32,
+            #[thisctx(vis = "pub(self)")]
+            hidden: i32,
+        },
+    }
+}
+
+fn main() {
+    let _ = error::Named {
+        visible: 0,
+        hidden: 0,
+    };
+}
@@ -0,0 +1,27 @@
+enum Error {
+    Io { source: std::io::Error },
+}
+
+mod message {
+    pub struct Message {
+        pub message: String,
+    }
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = message::Message)]
+struct MissingError {
+    message: String,
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Error::Io, source = std::io::Error)]
+struct SourceConflict {
+    source: String,
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Error::Io, source_field = source)]
+struct MissingSource;
+
+fn main() {}
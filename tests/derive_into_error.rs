@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use std::io;
+
+#[derive(Debug)]
+enum Error {
+    Io {
+        source: io::Error,
+        path: String,
+    },
+    Invalid {
+        line: usize,
+        column: usize,
+    },
+    Parse {
+        cause: std::num::ParseIntError,
+        input: String,
+    },
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Error::Io, source = io::Error)]
+struct ReadFile {
+    path: String,
+}
+
+impl ReadFile {
+    fn new(path: &str) -> Self {
+        Self {
+            path: path.trim().to_owned(),
+        }
+    }
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Error::Invalid, error = Error)]
+struct Invalid {
+    line: usize,
+    column: usize,
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Error::Parse, source = std::num::ParseIntError, source_field = cause)]
+struct ParseInt {
+    input: String,
+}
+
+#[derive(Debug)]
+struct Message {
+    message: &'static str,
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = Message)]
+struct MessageContext {
+    message: &'static str,
+}
+
+mod message {
+    #[derive(Debug)]
+    pub struct Message {
+        pub message: String,
+    }
+}
+
+#[derive(thisctx::IntoError)]
+#[into_error(target = message::Message, error = message::Message)]
+struct OwnedMessage {
+    message: &'static str,
+}
+
+#[test]
+fn derive_into_error() {
+    use thisctx::{IntoError, WithContext};
+
+    assert!(matches!(
+        Err::<(), _>(io::Error::from(io::ErrorKind::NotFound)).context(ReadFile::new(" /file ")),
+        Err(Error::Io { ref path, .. }) if path == "/file"
+    ));
+    assert!(matches!(
+        Invalid { line: 1, column: 2 }.build(),
+        Error::Invalid { line: 1, column: 2 }
+    ));
+    assert!(matches!(
+        "x".parse::<i32>().context(ParseInt { input: "x".to_owned() }),
+        Err(Error::Parse { ref input, .. }) if input == "x"
+    ));
+    assert_eq!(MessageContext { message: "hi" }.build().message, "hi");
+    // fields are converted to the types of the target
+    assert_eq!(OwnedMessage { message: "hi" }.build().message, "hi");
+}
@@ -0,0 +1,58 @@
+#![allow(dead_code)]
+
+use std::convert::TryFrom;
+use std::io;
+
+#[derive(Debug, thisctx::WithContext)]
+enum Error {
+    Io {
+        source: io::Error,
+        path: String,
+        #[thisctx(optional)]
+        retries: Option<u32>,
+    },
+    Parse(usize, usize),
+    Nested {
+        name: String,
+        source: Box<Error>,
+    },
+}
+
+#[derive(Debug, thisctx::WithContext)]
+#[thisctx(suffix = Context)]
+struct StructError {
+    message: String,
+    #[source]
+    inner: io::Error,
+}
+
+#[test]
+fn into_parts() {
+    use thisctx::IntoError;
+
+    let e = Io { path: "/file" }.into_error(io::Error::from(io::ErrorKind::NotFound));
+    let (ctx, source) = <(Io, io::Error)>::try_from(e).unwrap();
+    assert_eq!(ctx.path, "/file");
+    assert_eq!(source.kind(), io::ErrorKind::NotFound);
+    // re-raise with another source
+    assert!(matches!(
+        ctx.into_error(io::Error::from(io::ErrorKind::Other)),
+        Error::Io { ref source, .. } if source.kind() == io::ErrorKind::Other
+    ));
+
+    let e = Parse(1usize, 2usize).build();
+    assert!(<(Io, io::Error)>::try_from(e).is_err());
+    let e = Parse(1usize, 2usize).build();
+    let (Parse(line, column), thisctx::NoneSource) = <(Parse, _)>::try_from(e).unwrap();
+    assert_eq!((line, column), (1, 2));
+
+    let e = Nested { name: "outer" }.into_error(Parse(1usize, 2usize).build());
+    let (ctx, source) = <(Nested, Box<Error>)>::try_from(e).unwrap();
+    assert_eq!(ctx.name, "outer");
+    assert!(matches!(*source, Error::Parse(1, 2)));
+
+    let e = StructErrorContext { message: "message" }
+        .into_error(io::Error::from(io::ErrorKind::NotFound));
+    let (ctx, _) = <(StructErrorContext, io::Error)>::from(e);
+    assert_eq!(ctx.message, "message");
+}